@@ -1,6 +1,11 @@
 pub(crate) struct Cli {
     pub(crate) cwd: Option<camino::Utf8PathBuf>,
     pub(crate) action: Action,
+    /// Values of the repeatable filter options
+    pub(crate) managers: Vec<String>,
+    pub(crate) paths: Vec<String>,
+    pub(crate) deps: Vec<String>,
+    pub(crate) kinds: Vec<String>,
 }
 
 #[derive(Clone)]
@@ -10,13 +15,21 @@ pub(crate) enum Action {
     Edit,
     Summarize,
     Apply,
+    Hoist,
     Clean,
 }
 
 pub(crate) static USAGE: &str = concat!(
     "Usage: ",
     env!("CARGO_PKG_NAME"),
-    " [--cwd=DIR] <init | edit | apply | summarize | clean>"
+    " [--cwd=DIR] [FILTER]... <init | edit | apply | summarize | hoist | clean>\n",
+    "\n",
+    "Filters, which init, apply, and summarize honour:\n",
+    "  --manager=NAME  only dependencies of managers like cargo\n",
+    "  --path=GLOB     only files matching, like apps/web/**\n",
+    "  --dep=GLOB      only dependencies named like tokio*\n",
+    "  --kind=KIND     only dependencies of kinds like dev\n",
+    "Each may be repeated to match any of the values."
 );
 
 pub(crate) fn parse() -> Result<Cli, lexopt::Error> {
@@ -24,6 +37,10 @@ pub(crate) fn parse() -> Result<Cli, lexopt::Error> {
 
     let mut cwd = None;
     let mut action = None;
+    let mut managers = Vec::new();
+    let mut paths = Vec::new();
+    let mut deps = Vec::new();
+    let mut kinds = Vec::new();
     let mut parser = lexopt::Parser::from_env();
     while let Some(arg) = parser.next()? {
         match arg {
@@ -31,6 +48,11 @@ pub(crate) fn parse() -> Result<Cli, lexopt::Error> {
                 cwd = Some(parser.value()?.parse()?);
             }
 
+            Long("manager") => managers.push(parser.value()?.string()?),
+            Long("path") => paths.push(parser.value()?.string()?),
+            Long("dep") => deps.push(parser.value()?.string()?),
+            Long("kind") => kinds.push(parser.value()?.string()?),
+
             Short('h') | Long("help") => action = Some(Action::Usage { requested: true }),
             Value(v) if v == "help" => action = Some(Action::Usage { requested: true }),
 
@@ -38,6 +60,7 @@ pub(crate) fn parse() -> Result<Cli, lexopt::Error> {
             Value(v) if v == "edit" => action = Some(Action::Edit),
             Value(v) if v == "apply" => action = Some(Action::Apply),
             Value(v) if v == "summarize" => action = Some(Action::Summarize),
+            Value(v) if v == "hoist" => action = Some(Action::Hoist),
             Value(v) if v == "clean" => action = Some(Action::Clean),
 
             _ => return Err(arg.unexpected()),
@@ -47,5 +70,9 @@ pub(crate) fn parse() -> Result<Cli, lexopt::Error> {
     Ok(Cli {
         cwd,
         action: action.unwrap_or(Action::Usage { requested: false }),
+        managers,
+        paths,
+        deps,
+        kinds,
     })
 }
@@ -0,0 +1,184 @@
+//! User configuration, read from `pupgrade.toml` in the working directory.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use anyhow::Context as _;
+use facet::Facet;
+
+static CONFIG_FILE: &str = concat!(env!("CARGO_PKG_NAME"), ".toml");
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    #[facet(default)]
+    pub(crate) npm: Npm,
+    #[facet(default)]
+    pub(crate) python: Python,
+    #[facet(default)]
+    pub(crate) go: Go,
+    #[facet(default)]
+    pub(crate) rust: Rust,
+    #[facet(default)]
+    pub(crate) oci: Oci,
+    #[facet(default)]
+    pub(crate) mise: Mise,
+    #[facet(default)]
+    pub(crate) terraform: Terraform,
+    #[facet(default)]
+    pub(crate) walk: Walk,
+    /// Rules for finding versions in arbitrary files
+    #[facet(default)]
+    pub(crate) regex: Vec<RegexRule>,
+    /// Manager plugins to run besides the `pupgrade-manager-*` executables on
+    /// `PATH`, relative to the working directory
+    #[facet(default)]
+    pub(crate) plugins: Vec<String>,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Npm {
+    /// How to handle version ranges like `^1.2.0`
+    #[facet(default)]
+    pub(crate) ranges: RangePolicy,
+    /// Node release index used to check `engines.node`, defaulting to
+    /// <https://nodejs.org/dist/index.json>
+    #[facet(default)]
+    pub(crate) node_index: Option<String>,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Python {
+    /// Simple repository API to look up packages in, overriding
+    /// `PIP_INDEX_URL` and `UV_INDEX_URL`
+    #[facet(default)]
+    pub(crate) index_url: Option<String>,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Go {
+    /// Module proxy to query, overriding `GOPROXY`. Can be a `file://` URL.
+    #[facet(default)]
+    pub(crate) proxy: Option<String>,
+    /// Whether to run `go mod tidy` after applying updates
+    #[facet(default)]
+    pub(crate) tidy: bool,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Rust {
+    /// Server with the Rust dist manifests, overriding `RUSTUP_DIST_SERVER`
+    #[facet(default)]
+    pub(crate) dist_server: Option<String>,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Oci {
+    /// URLs to query instead of registry hosts, eg a mirror for `docker.io`
+    /// or `http://localhost:5000` for a local registry
+    #[facet(default)]
+    pub(crate) mirrors: HashMap<String, String>,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Mise {
+    /// Release list of the python.org downloads API, defaulting to
+    /// <https://www.python.org/api/v2/downloads/release/>
+    #[facet(default)]
+    pub(crate) python_releases: Option<String>,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Terraform {
+    /// Registry to look up providers without a hostname in, eg
+    /// `https://registry.opentofu.org`, defaulting to registry.terraform.io
+    #[facet(default)]
+    pub(crate) registry: Option<String>,
+}
+
+#[derive(Facet, Default)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Walk {
+    /// Whether to walk into directories holding their own git repository,
+    /// like submodules
+    #[facet(default)]
+    pub(crate) nested_repositories: bool,
+    /// Whether to walk through symlinks, scanning files by their real path
+    #[facet(default)]
+    pub(crate) follow_symlinks: bool,
+}
+
+#[derive(Facet)]
+#[facet(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct RegexRule {
+    /// Glob of the files to search, relative to the working directory
+    pub(crate) files: String,
+    /// Regex with a `version` capture group, and a `name` group unless
+    /// `dep-name` is set
+    pub(crate) pattern: String,
+    /// Name of the dependency for patterns without a `name` group
+    #[facet(default)]
+    pub(crate) dep_name: Option<String>,
+    pub(crate) datasource: Datasource,
+    /// Cargo registry name for `crates-io`, API URL for `github-releases`, or
+    /// base URL of short repository names for `git-tags`
+    #[facet(default)]
+    pub(crate) registry: Option<String>,
+}
+
+#[derive(Facet, Debug, Clone, Copy, PartialEq, Eq)]
+#[facet(rename_all = "kebab-case")]
+#[repr(u8)]
+#[expect(unused, reason = "only constructed when deserializing")]
+pub(crate) enum Datasource {
+    /// Crate versions from crates.io or the Cargo registry in `registry`
+    CratesIo,
+    /// The `latest` tag of npm packages, using `.npmrc` registries
+    Npm,
+    /// Version tags of a git repository
+    GitTags,
+    /// Releases of a GitHub repository like `owner/repo`
+    GithubReleases,
+}
+
+#[derive(Facet, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[facet(rename_all = "kebab-case")]
+#[repr(u8)]
+pub(crate) enum RangePolicy {
+    /// List newer versions without changing the range
+    #[default]
+    Report,
+    /// Raise the lower bound to the newest version, keeping the operator
+    #[expect(unused, reason = "only constructed when deserializing")]
+    Bump,
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let toml = match fs::read_to_string(CONFIG_FILE) {
+            Ok(toml) => toml,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err).context("reading config"),
+        };
+
+        let parsed = taplo::parser::parse(&toml);
+        if let Some(err) = parsed.errors.first() {
+            anyhow::bail!("parsing config: {err}");
+        }
+
+        // Round-trip through JSON to reuse facet for deserializing
+        let json = serde_json::to_string(&parsed.into_dom()).context("converting config")?;
+        let config = facet_json::from_str(&json)
+            .map_err(facet_json::DeserError::into_owned)
+            .context("deserializing config")?;
+        Ok(config)
+    }
+}
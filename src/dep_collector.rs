@@ -6,62 +6,168 @@ use std::fmt;
 use std::sync::Mutex;
 
 #[derive(Clone, Copy)]
-pub(crate) struct DepCollector<'a> {
+pub struct DepCollector<'a> {
     data: &'a DepsBuilder,
     manager: usize,
 }
 
-pub(crate) struct DepsBuilder {
+pub struct DepsBuilder {
     paths: boxcar::Vec<Utf8PathBuf>,
     kinds: Mutex<HashMap<String, (usize, String)>>,
     deps: boxcar::Vec<Dep>,
+    members: boxcar::Vec<(usize, MemberInit)>,
 }
 
 #[derive(Facet)]
-pub(crate) struct Deps {
+pub struct Deps {
+    /// Names of the managers that [`Dep::manager`] indexes, as registered
+    /// when scanning
+    #[facet(default)]
+    managers: Vec<String>,
     paths: Vec<Utf8PathBuf>,
     kinds: Vec<(String, String)>,
     deps: Vec<Dep>,
 }
 
 #[derive(Facet)]
-pub(crate) struct Dep {
-    pub(crate) manager: usize,
-    pub(crate) path: Option<usize>,
-    pub(crate) kind: Option<usize>,
+#[non_exhaustive]
+pub struct Dep {
+    pub manager: usize,
+    pub path: Option<usize>,
+    pub kind: Option<usize>,
     #[facet(skip_serializing_if = is_default, default)]
-    pub(crate) skip: bool,
+    pub skip: bool,
 
-    pub(crate) name: String,
+    pub name: String,
     #[facet(skip_serializing_if = is_default, default)]
-    pub(crate) renamed: Option<String>,
-    pub(crate) version: Version,
+    pub renamed: Option<String>,
+    /// Manager-specific name or URL of the registry the dependency comes from,
+    /// if not the default one
+    #[facet(skip_serializing_if = is_default, default)]
+    pub registry: Option<String>,
+    /// Manager-specific protocol the version was written with, eg `npm:` for
+    /// npm aliases
+    #[facet(skip_serializing_if = is_default, default)]
+    pub protocol: Option<String>,
+    pub version: Version,
     #[facet(skip_serializing_if = Updates::is_none, default)]
-    pub(crate) updates: Updates,
+    pub updates: Updates,
+    #[facet(skip_serializing_if = is_default, default)]
+    pub members: Vec<Member>,
+    #[facet(skip_serializing_if = is_default, default)]
+    pub notes: Vec<String>,
+    /// Versions to pick from in `edit` instead of the one found, newest first
+    #[facet(skip_serializing_if = is_default, default)]
+    pub candidates: Vec<Candidate>,
+}
+
+/// A version newer than the current one
+#[derive(Facet, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Candidate {
+    pub version: Version,
+    /// Release date like `2024-05-01`, if the registry has one
+    #[facet(skip_serializing_if = is_default, default)]
+    pub released: Option<String>,
+    /// Why it stands out, like `latest patch`, or empty if it doesn't
+    #[facet(skip_serializing_if = is_default, default)]
+    pub labels: Vec<String>,
+}
+
+impl Candidate {
+    /// An unlabelled candidate without a release date.
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            released: None,
+            labels: Vec::new(),
+        }
+    }
+}
+
+/// Somewhere that inherits a dependency declared elsewhere, eg a Cargo
+/// workspace member using `foo.workspace = true`
+#[derive(Facet, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Member {
+    pub path: Option<usize>,
+    pub kind: Option<usize>,
+    #[facet(skip_serializing_if = is_default, default)]
+    pub features: Vec<String>,
 }
 
 #[derive(Facet, Default)]
 #[repr(u8)]
-pub(crate) enum Updates {
+#[non_exhaustive]
+pub enum Updates {
     #[default]
     None,
-    #[expect(unused)]
     Failed,
     Found(Version),
 }
 
-pub(crate) struct DepInit {
-    pub(crate) path: Option<usize>,
-    pub(crate) kind: Option<usize>,
-    pub(crate) name: String,
-    pub(crate) renamed: Option<String>,
-    pub(crate) version: Version,
+#[non_exhaustive]
+pub struct DepInit {
+    pub path: Option<usize>,
+    pub kind: Option<usize>,
+    pub name: String,
+    pub renamed: Option<String>,
+    pub registry: Option<String>,
+    pub protocol: Option<String>,
+    pub version: Version,
 }
 
-#[derive(Facet)]
+impl DepInit {
+    /// A dependency on `name` at `version`, leaving the other fields empty.
+    pub fn new(name: impl Into<String>, version: Version) -> Self {
+        Self {
+            path: None,
+            kind: None,
+            name: name.into(),
+            renamed: None,
+            registry: None,
+            protocol: None,
+            version,
+        }
+    }
+}
+
+#[non_exhaustive]
+pub struct MemberInit {
+    /// Path of the dependency being inherited
+    pub parent_path: Utf8PathBuf,
+    /// Internal kind of the dependency being inherited
+    pub parent_kind: String,
+    /// Name of the dependency being inherited, or its renamed name if it has one
+    pub name: String,
+    pub path: Option<usize>,
+    pub kind: Option<usize>,
+    pub features: Vec<String>,
+}
+
+impl MemberInit {
+    /// A member inheriting `name` from the dependency of the internal kind
+    /// `parent_kind` at `parent_path`, leaving the other fields empty.
+    pub fn new(
+        parent_path: impl Into<Utf8PathBuf>,
+        parent_kind: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            parent_path: parent_path.into(),
+            parent_kind: parent_kind.into(),
+            name: name.into(),
+            path: None,
+            kind: None,
+            features: Vec::new(),
+        }
+    }
+}
+
+#[derive(Facet, Clone, PartialEq, Eq)]
 #[repr(u8)]
-#[expect(unused)]
-pub(crate) enum Version {
+#[non_exhaustive]
+pub enum Version {
     SemVer(String),
     GitCommit {
         repo: String,
@@ -72,56 +178,123 @@ pub(crate) enum Version {
         commit: String,
         tag: String,
     },
+    /// Container image tag pinned to the digest of its manifest, eg
+    /// `rust:1.83-slim@sha256:...`
+    OciPinnedTag {
+        image: String,
+        tag: String,
+        digest: String,
+    },
 }
 
 impl Deps {
-    pub(crate) fn serialize(self) -> String {
-        facet_json::to_string(&self)
+    pub(crate) fn serialize(&self) -> String {
+        facet_json::to_string(self)
     }
 
     pub(crate) fn deserialize(s: &str) -> Result<Self, facet_json::DeserError<'_>> {
         facet_json::from_str::<Deps>(s)
     }
 
-    pub(crate) fn deps(&self) -> &[Dep] {
+    pub fn deps(&self) -> &[Dep] {
         &self.deps
     }
 
-    pub(crate) fn deps_mut(&mut self) -> &mut [Dep] {
+    /// Records the managers that found the dependencies, for
+    /// [`resolve_managers`](Self::resolve_managers) after loading.
+    pub fn set_managers(&mut self, managers: &[Box<dyn crate::Manager>]) {
+        self.managers = managers
+            .iter()
+            .map(|manager| manager.name().to_owned())
+            .collect();
+    }
+
+    /// Points dependencies at `managers` by name, as the managers registered
+    /// may have changed since scanning, eg when installing a plugin. Skips
+    /// dependencies whose manager no longer exists.
+    pub fn resolve_managers(&mut self, managers: &[Box<dyn crate::Manager>]) -> anyhow::Result<()> {
+        if self.managers.is_empty() && !self.deps.is_empty() {
+            anyhow::bail!("state doesn't record its managers, run `init` again");
+        }
+
+        let resolved = self
+            .managers
+            .iter()
+            .map(|name| managers.iter().position(|manager| manager.name() == name))
+            .collect::<Vec<_>>();
+        for (name, id) in self.managers.iter().zip(&resolved) {
+            if id.is_none() {
+                let count = self
+                    .deps
+                    .iter()
+                    .filter(|dep| self.managers[dep.manager] == *name)
+                    .count();
+                if count > 0 {
+                    log::warn!("skipping {count} dependencies of unknown manager {name}");
+                }
+            }
+        }
+
+        self.deps
+            .retain_mut(|dep| match resolved.get(dep.manager).copied().flatten() {
+                Some(id) => {
+                    dep.manager = id;
+                    true
+                }
+                None => false,
+            });
+        self.set_managers(managers);
+        Ok(())
+    }
+
+    pub fn deps_mut(&mut self) -> &mut [Dep] {
         &mut self.deps
     }
 
-    pub(crate) fn path(&self, id: usize) -> &Utf8Path {
+    /// Keeps the dependencies `keep` returns true for, passing it the state to
+    /// look up paths and kinds in.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Self, &Dep) -> bool) {
+        let kept = self
+            .deps
+            .iter()
+            .map(|dep| keep(self, dep))
+            .collect::<Vec<_>>();
+        let mut kept = kept.into_iter();
+        self.deps.retain(|_| kept.next().unwrap());
+    }
+
+    pub fn path(&self, id: usize) -> &Utf8Path {
         &self.paths[id]
     }
 
-    pub(crate) fn internal_kind(&self, id: usize) -> &str {
+    pub fn internal_kind(&self, id: usize) -> &str {
         &self.kinds[id].0
     }
 
-    pub(crate) fn kind(&self, id: usize) -> &str {
+    pub fn kind(&self, id: usize) -> &str {
         &self.kinds[id].1
     }
 
-    pub(crate) fn dep_mut(&mut self, id: usize) -> &mut Dep {
+    pub fn dep_mut(&mut self, id: usize) -> &mut Dep {
         &mut self.deps[id]
     }
 }
 
 impl DepsBuilder {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             paths: boxcar::Vec::new(),
             kinds: Mutex::new(HashMap::new()),
             deps: boxcar::Vec::new(),
+            members: boxcar::Vec::new(),
         }
     }
 
-    pub(crate) fn count(&self) -> usize {
+    pub fn count(&self) -> usize {
         self.deps.count()
     }
 
-    pub(crate) fn collector(&self, manager: usize) -> DepCollector<'_> {
+    pub fn collector(&self, manager: usize) -> DepCollector<'_> {
         DepCollector {
             data: self,
             manager,
@@ -129,12 +302,18 @@ impl DepsBuilder {
     }
 }
 
+impl Default for DepsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DepCollector<'_> {
-    pub(crate) fn push_path(&self, path: Utf8PathBuf) -> usize {
+    pub fn push_path(&self, path: Utf8PathBuf) -> usize {
         self.data.paths.push(path)
     }
 
-    pub(crate) fn get_kind_id(&self, internal: String, display: impl FnOnce() -> String) -> usize {
+    pub fn get_kind_id(&self, internal: String, display: impl FnOnce() -> String) -> usize {
         let mut kinds = self.data.kinds.lock().unwrap();
         let next_id = kinds.len();
         kinds
@@ -143,7 +322,7 @@ impl DepCollector<'_> {
             .0
     }
 
-    pub(crate) fn push_dep(&self, init: DepInit) {
+    pub fn push_dep(&self, init: DepInit) {
         self.data.deps.push(Dep {
             manager: self.manager,
             path: init.path,
@@ -151,10 +330,21 @@ impl DepCollector<'_> {
             skip: false,
             name: init.name,
             renamed: init.renamed,
+            registry: init.registry,
+            protocol: init.protocol,
             version: init.version,
             updates: Updates::None,
+            members: Vec::new(),
+            notes: Vec::new(),
+            candidates: Vec::new(),
         });
     }
+
+    /// Records that a dependency declared elsewhere is inherited. This gets
+    /// linked to the inherited dependency once scanning finishes.
+    pub fn push_member(&self, init: MemberInit) {
+        self.data.members.push((self.manager, init));
+    }
 }
 
 impl fmt::Display for Version {
@@ -176,11 +366,22 @@ impl fmt::Display for Version {
         match self {
             Version::SemVer(semver) => f.write_str(semver),
             Version::GitCommit { commit, .. } => write_commit(f, commit),
+            // Some managers only look up the commit of the current tag when
+            // finding updates
+            Version::GitPinnedTag { commit, tag, .. } if commit.is_empty() => f.write_str(tag),
             Version::GitPinnedTag { commit, tag, .. } => {
                 f.write_str(tag)?;
                 f.write_str(" @ ")?;
                 write_commit(f, commit)
             }
+            Version::OciPinnedTag { tag, digest, .. } => {
+                f.write_str(tag)?;
+                f.write_str(" @ ")?;
+                let hash = digest
+                    .split_once(':')
+                    .map_or(digest.as_str(), |(_, hash)| hash);
+                write_commit(f, hash)
+            }
         }
     }
 }
@@ -190,7 +391,7 @@ impl Updates {
     ///
     /// [`None`]: Updates::None
     #[must_use]
-    pub(crate) fn is_none(&self) -> bool {
+    pub fn is_none(&self) -> bool {
         matches!(self, Self::None)
     }
 
@@ -198,7 +399,7 @@ impl Updates {
     ///
     /// [`Found`]: Updates::Found
     #[must_use]
-    pub(crate) fn is_found(&self) -> bool {
+    pub fn is_found(&self) -> bool {
         matches!(self, Self::Found(..))
     }
 }
@@ -208,15 +409,47 @@ impl From<DepsBuilder> for Deps {
         let kinds = builder.kinds.lock().unwrap();
         let mut kinds = kinds.iter().collect::<Vec<_>>();
         kinds.sort_unstable_by_key::<usize, _>(|(_, (id, _))| *id);
-        let kinds = kinds
+        let kinds: Vec<_> = kinds
             .into_iter()
             .map(|(internal, (_, display))| (internal.clone(), display.clone()))
             .collect();
 
+        let paths = builder.paths.into_iter().collect::<Vec<_>>();
+        let mut deps = builder.deps.into_iter().collect::<Vec<_>>();
+
+        let mut parents = HashMap::new();
+        for (id, dep) in deps.iter().enumerate() {
+            if let (Some(path), Some(kind)) = (dep.path, dep.kind) {
+                let name = dep.renamed.as_deref().unwrap_or(&dep.name).to_owned();
+                let kind = kinds[kind].0.as_str();
+                parents.insert((dep.manager, paths[path].as_path(), kind, name), id);
+            }
+        }
+
+        for (manager, member) in builder.members {
+            let key = (
+                manager,
+                member.parent_path.as_path(),
+                member.parent_kind.as_str(),
+                member.name,
+            );
+            if let Some(&id) = parents.get(&key) {
+                deps[id].members.push(Member {
+                    path: member.path,
+                    kind: member.kind,
+                    features: member.features,
+                });
+            } else {
+                let path = member.path.map(|id| paths[id].as_str()).unwrap_or_default();
+                log::warn!("{path}: could not find inherited dependency {}", key.3);
+            }
+        }
+
         Self {
-            paths: builder.paths.into_iter().collect(),
+            managers: Vec::new(),
+            paths,
             kinds,
-            deps: builder.deps.into_iter().collect(),
+            deps,
         }
     }
 }
@@ -3,7 +3,7 @@ use std::fmt::{self, Write as _};
 
 use dialoguer::{FuzzySelect, MultiSelect, Select};
 
-use crate::dep_collector::{Dep, Updates};
+use crate::dep_collector::{Candidate, Dep, Updates, Version};
 
 pub(crate) fn run(state: &mut crate::Deps) -> anyhow::Result<()> {
     let theme = dialoguer::theme::ColorfulTheme::default();
@@ -59,20 +59,40 @@ pub(crate) fn run(state: &mut crate::Deps) -> anyhow::Result<()> {
                 prompt.push_str("\n  ");
             }
             write!(prompt, "{}", DisplayFullDep::new(state, *id)).unwrap();
+            for note in &state.deps()[*id].notes {
+                write!(prompt, "\n    note: {note}").unwrap();
+            }
         }
 
-        let actions = Action::ALL;
+        let candidates = shared_candidates(state, ids);
+        let actions = Action::ALL
+            .iter()
+            .copied()
+            .filter(|action| !matches!(action, Action::Pick) || !candidates.is_empty())
+            .collect::<Vec<_>>();
         let action = Select::with_theme(&theme)
             .with_prompt(prompt)
-            .items(actions)
+            .items(&actions)
             .default(0)
             .interact()?;
 
+        let picked = match actions[action] {
+            Action::Pick => match pick_version(&theme, &candidates)? {
+                Some(version) => Some(version),
+                None => continue,
+            },
+            _ => None,
+        };
+
         for id in ids {
             let dep = state.dep_mut(*id);
             match actions[action] {
                 Action::Update => dep.skip = false,
                 Action::Skip => dep.skip = true,
+                Action::Pick => {
+                    dep.updates = Updates::Found(picked.clone().unwrap());
+                    dep.skip = false;
+                }
             }
         }
     }
@@ -80,6 +100,88 @@ pub(crate) fn run(state: &mut crate::Deps) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The candidates of the first dependency that every other one has too.
+fn shared_candidates(state: &crate::Deps, ids: &[usize]) -> Vec<Candidate> {
+    let Some((first, rest)) = ids.split_first() else {
+        return Vec::new();
+    };
+    state.deps()[*first]
+        .candidates
+        .iter()
+        .filter(|candidate| {
+            rest.iter().all(|id| {
+                state.deps()[*id]
+                    .candidates
+                    .iter()
+                    .any(|other| other.version == candidate.version)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Asks for one of the labelled candidates, or any of them after picking
+/// "All versions…".
+fn pick_version(
+    theme: &dialoguer::theme::ColorfulTheme,
+    candidates: &[Candidate],
+) -> anyhow::Result<Option<Version>> {
+    let labelled = candidates
+        .iter()
+        .filter(|candidate| !candidate.labels.is_empty())
+        .collect::<Vec<_>>();
+    let mut items = labelled
+        .iter()
+        .map(|candidate| DisplayCandidate(candidate).to_string())
+        .collect::<Vec<_>>();
+    if labelled.len() < candidates.len() {
+        items.push("All versions…".to_owned());
+    }
+
+    let Some(choice) = Select::with_theme(theme)
+        .with_prompt("Pick a version")
+        .items(&items)
+        .default(0)
+        .interact_opt()?
+    else {
+        return Ok(None);
+    };
+    if let Some(candidate) = labelled.get(choice) {
+        return Ok(Some(candidate.version.clone()));
+    }
+
+    let choice = FuzzySelect::with_theme(theme)
+        .with_prompt("Pick a version")
+        .items(candidates.iter().map(DisplayCandidate))
+        .interact_opt()?;
+    Ok(choice.map(|choice| candidates[choice].version.clone()))
+}
+
+struct DisplayCandidate<'a>(&'a Candidate);
+
+impl fmt::Display for DisplayCandidate<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.version)?;
+
+        let details = self
+            .0
+            .labels
+            .iter()
+            .cloned()
+            .chain(
+                self.0
+                    .released
+                    .as_ref()
+                    .map(|date| format!("released {date}")),
+            )
+            .collect::<Vec<_>>();
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 struct DisplayFullDep<'a> {
     state: &'a crate::Deps,
     dep: &'a Dep,
@@ -113,6 +215,13 @@ impl fmt::Display for DisplayFullDep<'_> {
             write!(f, "in {}", self.state.path(id).as_str())?;
         }
 
+        for (i, member) in self.dep.members.iter().enumerate() {
+            f.write_str(if i == 0 { ", inherited by " } else { ", " })?;
+            if let Some(id) = member.path {
+                f.write_str(self.state.path(id).as_str())?;
+            }
+        }
+
         let Updates::Found(update) = &self.dep.updates else {
             unreachable!()
         };
@@ -124,10 +233,11 @@ impl fmt::Display for DisplayFullDep<'_> {
 enum Action {
     Update,
     Skip,
+    Pick,
 }
 
 impl Action {
-    const ALL: &[Self] = &[Self::Update, Self::Skip];
+    const ALL: &[Self] = &[Self::Update, Self::Skip, Self::Pick];
 }
 
 impl fmt::Display for Action {
@@ -135,6 +245,7 @@ impl fmt::Display for Action {
         f.write_str(match self {
             Self::Update => "Update",
             Self::Skip => "Skip",
+            Self::Pick => "Pick version…",
         })
     }
 }
@@ -0,0 +1,121 @@
+//! Narrowing commands down to some managers, paths, dependencies, or kinds.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::Manager;
+use crate::dep_collector::{Dep, Deps};
+
+/// Which dependencies a command works on. Each part matches anything when
+/// empty, and otherwise any of its values has to match.
+#[derive(Default)]
+pub struct Filter {
+    /// Manager names, ignoring case
+    managers: Vec<String>,
+    /// Globs of the files declaring dependencies
+    paths: Option<GlobSet>,
+    /// Globs of dependency names
+    deps: Option<GlobSet>,
+    /// Kinds as shown in summaries, ignoring case
+    kinds: Vec<String>,
+}
+
+impl Filter {
+    pub fn new(
+        managers: Vec<String>,
+        paths: &[String],
+        deps: &[String],
+        kinds: Vec<String>,
+    ) -> Result<Self, globset::Error> {
+        let glob_set = |globs: &[String], parse: fn(&str) -> Result<Glob, globset::Error>| {
+            if globs.is_empty() {
+                return Ok(None);
+            }
+            let mut set = GlobSetBuilder::new();
+            for glob in globs {
+                set.add(parse(glob)?);
+            }
+            set.build().map(Some)
+        };
+
+        Ok(Self {
+            managers,
+            paths: glob_set(paths, crate::managers::glob)?,
+            deps: glob_set(deps, Glob::new)?,
+            kinds,
+        })
+    }
+
+    /// Fails on manager names that none of `managers` have.
+    pub fn check_managers(&self, managers: &[Box<dyn Manager>]) -> anyhow::Result<()> {
+        for name in &self.managers {
+            if !managers
+                .iter()
+                .any(|manager| manager.name().eq_ignore_ascii_case(name))
+            {
+                let known = managers
+                    .iter()
+                    .map(|manager| manager.name())
+                    .collect::<Vec<_>>();
+                anyhow::bail!(
+                    "unknown manager {name}, expected one of: {}",
+                    known.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn includes_manager(&self, name: &str) -> bool {
+        self.managers.is_empty()
+            || self
+                .managers
+                .iter()
+                .any(|manager| manager.eq_ignore_ascii_case(name))
+    }
+
+    /// Checks the name and kind of a dependency.
+    pub fn includes_declaration(&self, deps: &Deps, dep: &Dep) -> bool {
+        let name = self.deps.as_ref().is_none_or(|globs| {
+            globs.is_match(&dep.name) || dep.renamed.as_ref().is_some_and(|r| globs.is_match(r))
+        });
+
+        let kind = self.kinds.is_empty()
+            || dep.kind.is_some_and(|id| {
+                let kind = deps.kind(id);
+                // Also match kinds like `Runtime (cfg(unix))` by their start
+                let short = kind.split_once(" (").map_or(kind, |(short, _)| short);
+                self.kinds
+                    .iter()
+                    .any(|k| k.eq_ignore_ascii_case(kind) || k.eq_ignore_ascii_case(short))
+            });
+
+        name && kind
+    }
+
+    /// Checks every part of the filter, matching paths against the files
+    /// declaring the dependency and any workspace members inheriting it.
+    pub fn includes(&self, deps: &Deps, managers: &[Box<dyn Manager>], dep: &Dep) -> bool {
+        let manager = &managers[dep.manager];
+        let path = self.paths.is_none()
+            || self.includes_path(deps, &**manager, dep.path, dep.kind)
+            || dep
+                .members
+                .iter()
+                .any(|member| self.includes_path(deps, &**manager, member.path, member.kind));
+
+        self.includes_manager(manager.name()) && path && self.includes_declaration(deps, dep)
+    }
+
+    fn includes_path(
+        &self,
+        deps: &Deps,
+        manager: &dyn Manager,
+        path: Option<usize>,
+        kind: Option<usize>,
+    ) -> bool {
+        let (Some(globs), Some(path)) = (&self.paths, path) else {
+            return false;
+        };
+        globs.is_match(manager.manifest(deps, deps.path(path), kind))
+    }
+}
@@ -0,0 +1,173 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fs;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+
+use camino::Utf8PathBuf;
+use sha2::{Digest as _, Sha256};
+
+pub(crate) fn git_url<'a>(repo: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+    let repo = repo.into();
+    if repo.chars().filter(|c| *c == '/').count() == 1 {
+        Cow::Owned(format!("https://github.com/{repo}.git"))
+    } else {
+        repo
+    }
+}
+
+/// Repositories already fetched during this run
+static FETCHED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+pub(crate) fn open_repo(url: &str) -> gix::Repository {
+    let cache_name = {
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_bytes());
+        hex::encode(hasher.finalize())
+    };
+
+    let cache = dirs::cache_dir().unwrap();
+    let cache = Utf8PathBuf::from_path_buf(cache).unwrap();
+    let repo_dir = cache
+        .join(env!("CARGO_PKG_NAME"))
+        .join("git")
+        .join(cache_name);
+
+    let first_open = FETCHED.lock().unwrap().insert(url.to_owned());
+
+    if let Ok(true) = fs::exists(&repo_dir) {
+        let repo = gix::open(repo_dir).unwrap();
+        if !first_open {
+            return repo;
+        }
+
+        let direction = gix::remote::Direction::Fetch;
+        let remote = repo.find_default_remote(direction).unwrap().unwrap();
+        let connection = remote.connect(direction).unwrap();
+        let fetch = connection
+            .prepare_fetch(
+                gix::progress::Discard,
+                gix::remote::ref_map::Options::default(),
+            )
+            .unwrap();
+        fetch
+            .receive(gix::progress::Discard, &AtomicBool::new(false))
+            .unwrap();
+
+        repo
+    } else {
+        fs::create_dir_all(&repo_dir).unwrap();
+
+        gix::prepare_clone_bare(url, repo_dir)
+            .unwrap()
+            .fetch_only(gix::progress::Discard, &AtomicBool::new(false))
+            .unwrap()
+            .0
+    }
+}
+
+/// Whether `reference` is a full commit hash rather than a tag or branch.
+pub(crate) fn is_commit(reference: &str) -> bool {
+    reference.len() == 40 && reference.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Returns the tip of the remote's default branch.
+pub(crate) fn default_branch(repo: &gix::Repository) -> Option<gix::Commit<'_>> {
+    // Fetches only update remote tracking refs, so `HEAD` may be stale
+    match repo.find_reference("refs/remotes/origin/HEAD") {
+        Ok(mut head) => head.peel_to_commit().ok(),
+        Err(_) => repo.head_commit().ok(),
+    }
+}
+
+/// Returns the tip of a branch of the remote.
+pub(crate) fn branch<'a>(repo: &'a gix::Repository, name: &str) -> Option<gix::Commit<'a>> {
+    let mut branch = repo
+        .find_reference(&format!("refs/remotes/origin/{name}"))
+        .ok()?;
+    branch.peel_to_commit().ok()
+}
+
+/// Reads a file from the tree of the remote's default branch.
+pub(crate) fn read_file(repo: &gix::Repository, path: &str) -> Option<Vec<u8>> {
+    let tree = default_branch(repo)?.tree().ok()?;
+    let entry = tree.lookup_entry_by_path(path).ok()??;
+    Some(entry.object().ok()?.detach().data)
+}
+
+pub(crate) struct Tag {
+    pub(crate) commit: String,
+    pub(crate) name: String,
+}
+
+/// Finds the newest version-like tag, preferring more specific tags, eg
+/// `v1.2.3` over `v1` when both point to the same release.
+pub(crate) fn latest_tag(repo: &gix::Repository) -> Option<Tag> {
+    let refs = repo.references().unwrap();
+    let tags = refs.tags().unwrap();
+
+    let count_dots = |s: &str| s.chars().filter(|c| *c == '.').count();
+
+    let mut latest: Option<(Tag, gix::date::Time)> = None;
+    for mut tag in tags.filter_map(Result::ok) {
+        let name: &str = tag.name().shorten().try_into().unwrap();
+        let without_v = name.strip_prefix('v').unwrap_or(name);
+
+        if !without_v.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let name = name.to_owned();
+
+        let commit = tag.peel_to_commit().unwrap();
+        let time = commit.author().unwrap().time().unwrap();
+        if let Some((latest, latest_time)) = latest.as_ref()
+            && (count_dots(&name) < count_dots(&latest.name) || time < *latest_time)
+        {
+            continue;
+        }
+
+        let commit = hex::encode(commit.id.as_slice());
+
+        latest = Some((Tag { commit, name }, time));
+    }
+
+    latest.map(|(tag, _)| tag)
+}
+
+/// Resolves the commit a tag points to.
+pub(crate) fn tag_commit(repo: &gix::Repository, tag: &str) -> Option<String> {
+    let mut tag = repo.find_reference(&format!("refs/tags/{tag}")).ok()?;
+    let commit = tag.peel_to_commit().ok()?;
+    Some(hex::encode(commit.id.as_slice()))
+}
+
+/// Lists the names of every tag.
+pub(crate) fn tags(repo: &gix::Repository) -> Vec<String> {
+    let refs = repo.references().unwrap();
+    let tags = refs.tags().unwrap();
+    tags.filter_map(Result::ok)
+        .map(|tag| tag.name().shorten().to_string())
+        .collect()
+}
+
+/// Finds the most specific version-like tag pointing to `commit`.
+pub(crate) fn commit_tag(repo: &gix::Repository, commit: &str) -> Option<String> {
+    let refs = repo.references().unwrap();
+    let tags = refs.tags().unwrap();
+
+    let count_dots = |s: &str| s.chars().filter(|c| *c == '.').count();
+
+    tags.filter_map(Result::ok)
+        .filter_map(|mut tag| {
+            let name = tag.name().shorten().to_string();
+            let without_v = name.strip_prefix('v').unwrap_or(&name);
+            if !without_v.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+
+            let peeled = tag.peel_to_commit().ok()?;
+            (hex::encode(peeled.id.as_slice()) == commit).then_some(name)
+        })
+        .max_by_key(|name| count_dots(name))
+}
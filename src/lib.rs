@@ -0,0 +1,184 @@
+//! Library behind the `pupgrade` binary.
+//!
+//! Support for another package format is a type implementing [`Manager`].
+//! Passing it to [`run`] gives a binary with the usual commands that knows
+//! about it alongside the managers from [`managers::all`]. The steps of those
+//! commands are available on their own too: [`walk`] and [`scan`] to find
+//! dependencies, [`load_state`] and [`save_state`] for the state file,
+//! [`apply`], and [`summary::write_markdown`].
+
+mod cli;
+pub mod config;
+mod dep_collector;
+mod editor;
+mod filter;
+mod git;
+pub mod managers;
+pub mod summary;
+mod walker;
+mod yaml;
+
+use std::io::{self, BufWriter};
+
+use anyhow::Context as _;
+use camino::Utf8Path;
+
+pub use self::dep_collector::{
+    Candidate, Dep, DepCollector, DepInit, Deps, DepsBuilder, Member, MemberInit, Updates, Version,
+};
+pub use self::filter::Filter;
+pub use self::managers::Manager;
+pub use self::walker::walk;
+
+/// Where `init` stores the dependencies it found, relative to the working
+/// directory
+pub static STATE_FILE: &str = concat!(".", env!("CARGO_PKG_NAME"), ".json");
+
+/// Runs the command line interface, with `extra` managers registered after
+/// the built-in ones.
+///
+/// The state file refers to managers by name, so `extra` managers need names
+/// that differ from the built-in ones and each other.
+pub fn run(extra: Vec<Box<dyn Manager>>) -> anyhow::Result<()> {
+    let cli = cli::parse()?;
+
+    let cwd = if let Some(cwd) = cli.cwd {
+        std::env::set_current_dir(&cwd).context("setting cwd")?;
+        cwd
+    } else {
+        std::env::current_dir()
+            .context("getting cwd")?
+            .try_into()
+            .context("converting cwd to UTF-8")?
+    };
+
+    let config = config::Config::load()?;
+    let mut managers = managers::all(&config)?;
+    managers.extend(extra);
+
+    let filter = Filter::new(cli.managers, &cli.paths, &cli.deps, cli.kinds)?;
+    filter.check_managers(&managers)?;
+    let load_filtered_state = || -> anyhow::Result<Deps> {
+        let mut state = load_state()?;
+        state.resolve_managers(&managers)?;
+        state.retain(|state, dep| filter.includes(state, &managers, dep));
+        Ok(state)
+    };
+
+    match cli.action {
+        cli::Action::Usage { requested } => {
+            eprintln!("{}", cli::USAGE);
+            if !requested {
+                std::process::exit(1);
+            }
+        }
+
+        cli::Action::Init => save_state(&scan(&cwd, &managers, &config, &filter))?,
+
+        cli::Action::Edit => {
+            let mut state = load_state()?;
+            editor::run(&mut state)?;
+            save_state(&state)?;
+        }
+
+        cli::Action::Apply => apply(&load_filtered_state()?, &managers),
+
+        cli::Action::Summarize => {
+            let stderr = io::stderr().lock();
+            let state = load_filtered_state()?;
+            summary::write_markdown(&state, &managers, &mut BufWriter::new(stderr))?;
+        }
+
+        cli::Action::Hoist => managers::cargo::hoist::run(&cwd, &config)?,
+
+        cli::Action::Clean => match std::fs::remove_file(STATE_FILE) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        },
+    }
+
+    Ok(())
+}
+
+/// Sets up logging to stderr, filtered by `PUPGRADE_LOG`.
+pub fn init_logger() {
+    let env = env_logger::Env::new()
+        .filter("PUPGRADE_LOG")
+        .write_style("COLOR");
+    env_logger::Builder::from_env(env)
+        .format_timestamp_millis()
+        .init();
+}
+
+/// Finds the dependencies under `root` matching `filter`, and any updates for
+/// them.
+pub fn scan(
+    root: &Utf8Path,
+    managers: &[Box<dyn Manager>],
+    config: &config::Config,
+    filter: &Filter,
+) -> Deps {
+    let files = walk(root, managers, config, filter);
+
+    let deps = DepsBuilder::new();
+    for (manager_id, paths) in files.iter().enumerate() {
+        let manager = &managers[manager_id];
+        for path in paths {
+            manager.scan_file(path, deps.collector(manager_id));
+        }
+    }
+
+    log::info!("Found {} dependencies", deps.count());
+
+    let mut deps = Deps::from(deps);
+    deps.set_managers(managers);
+    // Paths are only checked now, as workspace members need their root
+    deps.retain(|deps, dep| filter.includes(deps, managers, dep));
+
+    for dep in deps.deps_mut() {
+        log::info!("Finding updates for {}", &dep.name);
+        let mut notes = Vec::new();
+        let mut candidates = Vec::new();
+        dep.updates = managers[dep.manager].find_updates(dep, &mut notes, &mut candidates);
+        dep.notes = notes;
+        dep.candidates = candidates;
+    }
+
+    deps
+}
+
+/// Applies every update that was found and not skipped.
+pub fn apply(state: &Deps, managers: &[Box<dyn Manager>]) {
+    let mut applied = vec![Vec::new(); managers.len()];
+    for dep in state.deps() {
+        if !dep.skip
+            && let Updates::Found(version) = &dep.updates
+        {
+            managers[dep.manager].apply(state, dep, version);
+            applied[dep.manager].push(dep);
+        }
+    }
+
+    for (manager, applied) in managers.iter().zip(&applied) {
+        if !applied.is_empty() {
+            manager.after_apply(state, applied);
+        }
+    }
+}
+
+/// Reads the state file, whose dependencies need
+/// [`resolve_managers`](Deps::resolve_managers) before being passed to
+/// [`apply`] or [`summary::write_markdown`].
+pub fn load_state() -> anyhow::Result<Deps> {
+    let raw = std::fs::read_to_string(STATE_FILE).context("reading state")?;
+    let deps = Deps::deserialize(&raw)
+        .map_err(facet_json::DeserError::into_owned)
+        .context("deserializing state")?;
+    Ok(deps)
+}
+
+pub fn save_state(deps: &Deps) -> anyhow::Result<()> {
+    std::fs::write(STATE_FILE, deps.serialize()).context("writing state")?;
+    Ok(())
+}
@@ -1,24 +1,31 @@
+pub(crate) mod hoist;
+pub(super) mod registry;
+
+use std::collections::HashMap;
 use std::fmt;
 use std::fs;
-use std::io::{BufRead as _, BufReader};
+use std::sync::OnceLock;
 
-use camino::Utf8Path;
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 use facet::Facet;
 use taplo::dom::node::DomNode as _;
 use taplo::dom::{Node, node};
 
 use crate::DepCollector;
-use crate::dep_collector::{Dep, DepInit, Deps, Updates, Version};
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, MemberInit, Updates, Version};
 
-pub(super) struct Manager;
+#[derive(Default)]
+pub(super) struct Manager {
+    registries: OnceLock<registry::Registries>,
+}
 
 impl super::Manager for Manager {
     fn name(&self) -> &'static str {
         "Cargo"
     }
 
-    fn walk_file(&self, path: &Utf8Path) -> bool {
-        path.file_name().is_some_and(|name| name == "Cargo.toml")
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(&["**/Cargo.toml"])
     }
 
     fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
@@ -31,11 +38,16 @@ impl super::Manager for Manager {
 
         let get_root_table = |key| get_table(root, &[], key, path);
 
+        let workspace = find_workspace_root(root, path);
+        let scan_inner = |kind_id, table: &node::Table| {
+            scan_inner(collector, path_id, kind_id, workspace.as_deref(), table);
+        };
+
         if let Some(workspace) = get_root_table("workspace")
             && let Some(dependencies) = get_table(&workspace, &["workspace"], "dependencies", path)
         {
             let kind_id = collector.get_kind_id("workspace".to_owned(), || "Workspace".to_owned());
-            scan_inner(collector, path_id, kind_id, &dependencies);
+            scan_inner(kind_id, &dependencies);
         }
 
         for (key, display) in [
@@ -45,7 +57,7 @@ impl super::Manager for Manager {
         ] {
             if let Some(table) = get_root_table(key) {
                 let kind_id = collector.get_kind_id(key.to_owned(), || display.to_owned());
-                scan_inner(collector, path_id, kind_id, &table);
+                scan_inner(kind_id, &table);
             }
         }
 
@@ -68,7 +80,7 @@ impl super::Manager for Manager {
                     let kind_id = collector.get_kind_id(format!("{key}\0{target}"), || {
                         format!("{display} ({target})")
                     });
-                    scan_inner(collector, path_id, kind_id, &dependencies);
+                    scan_inner(kind_id, &dependencies);
                 }
             }
         });
@@ -77,51 +89,67 @@ impl super::Manager for Manager {
             let kind_id = collector.get_kind_id(format!("patch\0{registry}"), || {
                 format!("Patch ({registry})")
             });
-            scan_inner(collector, path_id, kind_id, table);
+            scan_inner(kind_id, table);
         });
     }
 
-    fn find_updates(&self, dep: &crate::Dep) -> Updates {
+    fn find_updates(
+        &self,
+        dep: &crate::Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
         match &dep.version {
             Version::SemVer(current) => {
-                let host = "https://index.crates.io";
-
-                // NOTE: assumes name is ascii
-                let uri = match dep.name.len() {
-                    0 => unreachable!(),
-                    1 => format!("{host}/1/{}", &dep.name),
-                    2 => format!("{host}/2/{}", &dep.name),
-                    3 => format!("{host}/3/{}/{}", &dep.name[0..1], &dep.name),
-                    _ => format!(
-                        "{host}/{}/{}/{}",
-                        &dep.name[0..2],
-                        &dep.name[2..4],
-                        &dep.name
-                    ),
+                let registries = self.registries.get_or_init(registry::Registries::load);
+                let Some(index) = registries.resolve(dep.registry.as_deref()) else {
+                    return Updates::Failed;
+                };
+                let Some(index) = index.fetch(&dep.name) else {
+                    return Updates::Failed;
                 };
 
                 let current = current.strip_prefix('=').unwrap_or(current);
                 let is_prerelease = |v: &str| v.contains('-');
                 let current_prelease = is_prerelease(current);
 
-                let mut response = ureq::get(uri).call().unwrap();
-                let body = response.body_mut().as_reader();
-                let mut crates = BufReader::new(body)
+                let mut crates = index
                     .lines()
-                    .filter_map(|line| facet_json::from_str(&line.ok()?).ok())
+                    .filter_map(|line| facet_json::from_str(line).ok())
                     .filter(|c: &Crate| current_prelease || !is_prerelease(&c.vers))
                     .collect::<Vec<_>>();
 
-                let Crate { vers: latest } = crates.pop().unwrap();
+                let latest = crates.pop().unwrap();
 
-                if current == latest {
+                if current == latest.vers {
                     Updates::None
                 } else {
-                    Updates::Found(Version::SemVer(format!("={latest}")))
+                    for feature in dep.members.iter().flat_map(|member| &member.features) {
+                        let note = format!(
+                            "feature `{feature}` enabled by a member does not exist in {}",
+                            latest.vers
+                        );
+                        if !latest.has_feature(feature) && !notes.contains(&note) {
+                            notes.push(note);
+                        }
+                    }
+
+                    // The index has no release dates
+                    let releases = crates
+                        .iter()
+                        .chain([&latest])
+                        .filter(|c| !c.yanked)
+                        .map(|c| (c.vers.clone(), None));
+                    *candidates = super::candidates(current, releases, |version| {
+                        Version::SemVer(format!("={version}"))
+                    });
+
+                    Updates::Found(Version::SemVer(format!("={}", latest.vers)))
                 }
             }
             Version::GitCommit { .. } => todo!(),
             Version::GitPinnedTag { .. } => todo!(),
+            Version::OciPinnedTag { .. } => unreachable!(),
         }
     }
 
@@ -163,6 +191,7 @@ impl super::Manager for Manager {
             }
             Version::GitCommit { .. } => todo!(),
             Version::GitPinnedTag { .. } => todo!(),
+            Version::OciPinnedTag { .. } => unreachable!(),
         }
 
         replacements.sort_by_key(|(range, _)| range.start());
@@ -173,14 +202,38 @@ impl super::Manager for Manager {
 
         fs::write(path, toml).unwrap();
     }
+
+    fn manifest(&self, _deps: &Deps, path: &Utf8Path, _kind: Option<usize>) -> Utf8PathBuf {
+        path.join("Cargo.toml")
+    }
 }
 
-fn scan_inner(collector: DepCollector, path_id: usize, kind_id: usize, table: &node::Table) {
+fn scan_inner(
+    collector: DepCollector,
+    path_id: usize,
+    kind_id: usize,
+    workspace: Option<&Utf8Path>,
+    table: &node::Table,
+) {
     use crate::dep_collector::Version;
 
-    for (name, meta) in table.entries().read().iter() {
+    for (key, meta) in table.entries().read().iter() {
+        let mut name = key.value().to_owned();
+        let mut renamed = None;
+        let mut registry = None;
+
         let version = match meta {
             Node::Table(meta) => {
+                if let Some(package) = meta.get("package") {
+                    let package = package.as_str().unwrap();
+                    renamed = Some(name);
+                    name = package.value().to_owned();
+                }
+
+                if let Some(index) = meta.get("registry-index").or_else(|| meta.get("registry")) {
+                    registry = Some(index.as_str().unwrap().value().to_owned());
+                }
+
                 if let Some(version) = meta.get("version") {
                     let version = version.as_str().unwrap();
                     Version::SemVer(version.value().to_owned())
@@ -196,9 +249,21 @@ fn scan_inner(collector: DepCollector, path_id: usize, kind_id: usize, table: &n
                     //     tag,
                     // }
                 } else {
-                    if let Some(workspace) = meta.get("workspace") {
-                        let workspace = workspace.as_bool().unwrap();
-                        if workspace.value() {
+                    if let Some(inherit) = meta.get("workspace") {
+                        let inherit = inherit.as_bool().unwrap();
+                        if inherit.value() {
+                            if let Some(workspace) = workspace {
+                                collector.push_member(MemberInit {
+                                    parent_path: workspace.to_owned(),
+                                    parent_kind: "workspace".to_owned(),
+                                    name: key.value().to_owned(),
+                                    path: Some(path_id),
+                                    kind: Some(kind_id),
+                                    features: get_features(meta),
+                                });
+                            } else {
+                                log::warn!("could not find workspace for {}", key.value());
+                            }
                             continue;
                         }
                     }
@@ -215,16 +280,107 @@ fn scan_inner(collector: DepCollector, path_id: usize, kind_id: usize, table: &n
         collector.push_dep(DepInit {
             path: Some(path_id),
             kind: Some(kind_id),
-            name: name.value().to_owned(),
-            renamed: None,
+            name,
+            renamed,
+            registry,
+            protocol: None,
             version,
         });
     }
 }
 
+/// Finds the directory of the workspace root a manifest belongs to.
+fn find_workspace_root(root: &node::Table, path: &Utf8Path) -> Option<Utf8PathBuf> {
+    let dir = path.parent().unwrap();
+
+    if let Some(package) = get_table(root, &[], "package", path)
+        && let Some(workspace) = package.get("workspace")
+        && let Some(workspace) = workspace.as_str()
+    {
+        return Some(normalize(&dir.join(workspace.value())));
+    }
+
+    for ancestor in dir.ancestors() {
+        let manifest = ancestor.join("Cargo.toml");
+        let Ok(toml) = fs::read_to_string(&manifest) else {
+            continue;
+        };
+
+        let dom = taplo::parser::parse(&toml).into_dom();
+        if dom
+            .as_table()
+            .is_some_and(|root| root.get("workspace").is_some())
+        {
+            return Some(ancestor.to_owned());
+        }
+    }
+
+    None
+}
+
+/// Lexically resolves `..` components in a relative path.
+fn normalize(path: &Utf8Path) -> Utf8PathBuf {
+    let mut out = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::ParentDir => {
+                out.pop();
+            }
+            Utf8Component::CurDir => {}
+            component => out.push(component),
+        }
+    }
+    out
+}
+
+fn get_features(meta: &node::Table) -> Vec<String> {
+    let Some(Node::Array(features)) = meta.get("features") else {
+        return Vec::new();
+    };
+
+    features
+        .items()
+        .read()
+        .iter()
+        .filter_map(|feature| Some(feature.as_str()?.value().to_owned()))
+        .collect()
+}
+
 #[derive(Facet)]
 struct Crate {
     vers: String,
+    #[facet(default)]
+    yanked: bool,
+    #[facet(default)]
+    features: HashMap<String, Vec<String>>,
+    #[facet(default)]
+    features2: HashMap<String, Vec<String>>,
+    #[facet(default)]
+    deps: Vec<CrateDep>,
+}
+
+#[derive(Facet)]
+struct CrateDep {
+    name: String,
+    #[facet(default)]
+    optional: bool,
+}
+
+impl Crate {
+    fn has_feature(&self, feature: &str) -> bool {
+        let mut explicit = self.features.iter().chain(&self.features2);
+        if explicit.clone().any(|(name, _)| name == feature) {
+            return true;
+        }
+
+        // Optional dependencies get an implicit feature unless something
+        // refers to them with the `dep:` prefix
+        let dep_prefixed = format!("dep:{feature}");
+        self.deps
+            .iter()
+            .any(|dep| dep.optional && dep.name == feature)
+            && !explicit.any(|(_, enables)| enables.contains(&dep_prefixed))
+    }
 }
 
 struct FullKey<'a> {
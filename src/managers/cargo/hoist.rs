@@ -0,0 +1,545 @@
+//! Finds crates pinned directly in several members of the same workspace and
+//! moves them into `[workspace.dependencies]`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::ops::Range;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use dialoguer::MultiSelect;
+use taplo::dom::node::{DomNode as _, TableKind};
+use taplo::dom::{Node, node};
+
+use super::get_table;
+
+/// Keys that stay in the member manifest instead of moving to the workspace.
+const MEMBER_KEYS: &[&str] = &["features", "optional", "default-features"];
+
+pub(crate) fn run(root: &Utf8Path, config: &crate::config::Config) -> anyhow::Result<()> {
+    let managers: [Box<dyn crate::Manager>; 1] = [Box::new(super::Manager::default())];
+    let files = crate::walker::walk(root, &managers, config, &crate::Filter::default());
+
+    let manifests = files[0]
+        .iter()
+        .map(|path| Manifest::read(path))
+        .collect::<Vec<_>>();
+
+    let mut entries = Vec::new();
+    for (id, manifest) in manifests.iter().enumerate() {
+        if let Some(workspace) = find_workspace(&manifests, id) {
+            manifest.collect_entries(id, workspace, &mut entries);
+        }
+    }
+
+    let candidates = find_candidates(&manifests, &entries);
+    if candidates.is_empty() {
+        log::info!("Nothing to hoist");
+        return Ok(());
+    }
+
+    let theme = dialoguer::theme::ColorfulTheme::default();
+    let items = candidates.iter().map(|candidate| {
+        let display = DisplayCandidate {
+            manifests: &manifests,
+            entries: &entries,
+            candidate,
+        };
+        (display, true)
+    });
+    let selected = MultiSelect::with_theme(&theme)
+        .with_prompt("Select dependencies to move into [workspace.dependencies]")
+        .items_checked(items)
+        .report(false)
+        .interact()?;
+
+    // Each workspace gets one insertion, so a missing table is only added once
+    let mut lines: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for &id in &selected {
+        let candidate = &candidates[id];
+        if !candidate.existing {
+            let first = &entries[candidate.entries[0]];
+            lines
+                .entry(candidate.workspace)
+                .or_default()
+                .push(first.workspace_line());
+        }
+    }
+
+    let mut edits: HashMap<usize, Vec<(Range<usize>, String)>> = HashMap::new();
+    let mut failed = HashSet::new();
+    for (workspace, lines) in lines {
+        match manifests[workspace].insert_workspace_dependencies(&lines) {
+            Some(edit) => edits.entry(workspace).or_default().push(edit),
+            None => {
+                failed.insert(workspace);
+            }
+        }
+    }
+
+    for id in selected {
+        let candidate = &candidates[id];
+        if !candidate.existing && failed.contains(&candidate.workspace) {
+            continue;
+        }
+
+        for &entry in &candidate.entries {
+            let entry = &entries[entry];
+            edits
+                .entry(entry.manifest)
+                .or_default()
+                .push(entry.member_edit());
+        }
+    }
+
+    for (id, mut edits) in edits {
+        let manifest = &manifests[id];
+        let mut toml = manifest.toml.clone();
+
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        for (range, with) in edits {
+            toml.replace_range(range, &with);
+        }
+
+        let path = manifest.dir.join("Cargo.toml");
+        log::info!("Updating {path}");
+        fs::write(path, toml)?;
+    }
+
+    Ok(())
+}
+
+struct Manifest {
+    dir: Utf8PathBuf,
+    toml: String,
+    root: node::Table,
+}
+
+/// A dependency pinned directly in a member manifest.
+struct Entry {
+    manifest: usize,
+    workspace: usize,
+    key: String,
+    /// Raw key as written, including any quotes
+    raw_key: String,
+    style: Style,
+    /// Range covering the whole entry, from the key to the end of the value
+    range: Range<usize>,
+    /// `(key, raw value)` pairs that move to the workspace, in source order
+    hoisted: Vec<(String, String)>,
+    /// `(key, raw value)` pairs that stay in the member, in source order
+    kept: Vec<(String, String)>,
+}
+
+enum Style {
+    /// `foo = "=1.0.0"`
+    String,
+    /// `foo = { version = "=1.0.0" }`
+    Inline,
+    /// `[dependencies.foo]`, where `range` only covers the body
+    Table,
+}
+
+struct Candidate {
+    workspace: usize,
+    /// Whether the workspace already has a matching entry
+    existing: bool,
+    entries: Vec<usize>,
+}
+
+impl Manifest {
+    fn read(path: &Utf8Path) -> Self {
+        let toml = fs::read_to_string(path).unwrap();
+        let dom = taplo::parser::parse(&toml).into_dom();
+        let root = dom.as_table().unwrap().clone();
+
+        Self {
+            dir: path.parent().unwrap().to_owned(),
+            toml,
+            root,
+        }
+    }
+
+    fn path(&self) -> Utf8PathBuf {
+        self.dir.join("Cargo.toml")
+    }
+
+    fn workspace(&self) -> Option<node::Table> {
+        get_table(&self.root, &[], "workspace", &self.path())
+    }
+
+    fn is_package(&self) -> bool {
+        self.root.get("package").is_some()
+    }
+
+    fn text(&self, node: &Node) -> &str {
+        let range = node.text_ranges(true).next().unwrap();
+        &self.toml[usize::from(range.start())..usize::from(range.end())]
+    }
+
+    /// Whether `member` is included by this manifest's `[workspace]`.
+    fn includes(&self, member: &Utf8Path) -> bool {
+        let Some(workspace) = self.workspace() else {
+            return false;
+        };
+
+        if member == self.dir {
+            return self.is_package();
+        }
+
+        let Ok(relative) = member.strip_prefix(&self.dir) else {
+            return false;
+        };
+
+        let strings = |key| {
+            let mut out = Vec::new();
+            if let Some(Node::Array(array)) = workspace.get(key) {
+                for item in array.items().read().iter() {
+                    if let Some(item) = item.as_str() {
+                        let item = item.value();
+                        out.push(item.strip_prefix("./").unwrap_or(item).to_owned());
+                    }
+                }
+            }
+            out
+        };
+
+        let mode = gix::glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL;
+        let included = strings("members").iter().any(|pattern| {
+            gix::glob::wildmatch(pattern.as_str().into(), relative.as_str().into(), mode)
+        });
+        let excluded = strings("exclude")
+            .iter()
+            .any(|prefix| relative.starts_with(prefix));
+
+        included && !excluded
+    }
+
+    fn collect_entries(&self, id: usize, workspace: usize, out: &mut Vec<Entry>) {
+        if !self.is_package() {
+            return;
+        }
+
+        let path = self.path();
+        let kinds = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+        for key in kinds {
+            if let Some(table) = get_table(&self.root, &[], key, &path) {
+                self.collect_table(id, workspace, &table, out);
+            }
+        }
+
+        if let Some(targets) = get_table(&self.root, &[], "target", &path) {
+            for (target, table) in targets.entries().read().iter() {
+                let Some(table) = table.as_table() else {
+                    continue;
+                };
+
+                for key in kinds {
+                    let parents = ["target", target.value()];
+                    if let Some(table) = get_table(table, &parents, key, &path) {
+                        self.collect_table(id, workspace, &table, out);
+                    }
+                }
+            }
+        }
+    }
+
+    fn collect_table(
+        &self,
+        id: usize,
+        workspace: usize,
+        table: &node::Table,
+        out: &mut Vec<Entry>,
+    ) {
+        for (key, value) in table.entries().read().iter() {
+            let Some(key_range) = key.text_ranges().next() else {
+                continue;
+            };
+            let key_start = usize::from(key_range.start());
+            let raw_key = self.toml[key_start..usize::from(key_range.end())].to_owned();
+            let key = key.value().to_owned();
+
+            let mut hoisted = Vec::new();
+            let mut kept = Vec::new();
+            let (style, range) = match value {
+                Node::Str(_) => {
+                    hoisted.push(("version".to_owned(), self.text(value).to_owned()));
+                    let end = value.text_ranges(true).next().unwrap().end();
+                    (Style::String, key_start..usize::from(end))
+                }
+                Node::Table(meta) => {
+                    let entries = meta.entries().read();
+                    if entries.iter().all(|(key, _)| key.value() != "version")
+                        || entries
+                            .iter()
+                            .any(|(key, _)| matches!(key.value(), "workspace" | "path" | "git"))
+                    {
+                        continue;
+                    }
+
+                    for (meta_key, meta_value) in entries.iter() {
+                        let pair = (
+                            meta_key.value().to_owned(),
+                            self.text(meta_value).to_owned(),
+                        );
+                        if MEMBER_KEYS.contains(&meta_key.value()) {
+                            kept.push(pair);
+                        } else {
+                            hoisted.push(pair);
+                        }
+                    }
+
+                    match meta.kind() {
+                        TableKind::Inline => {
+                            let end = value.text_ranges(true).next().unwrap().end();
+                            (Style::Inline, key_start..usize::from(end))
+                        }
+                        TableKind::Regular => {
+                            let header = meta.syntax().unwrap().text_range();
+                            let end = value.text_ranges(true).next().unwrap().end();
+                            (Style::Table, usize::from(header.end())..usize::from(end))
+                        }
+                        TableKind::Pseudo => {
+                            log::warn!(
+                                "{path}: skipping dotted keys for {key}",
+                                path = self.path()
+                            );
+                            continue;
+                        }
+                    }
+                }
+                _ => continue,
+            };
+
+            out.push(Entry {
+                manifest: id,
+                workspace,
+                key,
+                raw_key,
+                style,
+                range,
+                hoisted,
+                kept,
+            });
+        }
+    }
+
+    /// Returns the `(key, raw value)` pairs of an existing workspace dependency.
+    fn workspace_dependency(&self, key: &str) -> Option<Vec<(String, String)>> {
+        let path = self.path();
+        let workspace = self.workspace()?;
+        let dependencies = get_table(&workspace, &["workspace"], "dependencies", &path)?;
+
+        match dependencies.get(key)? {
+            value @ Node::Str(_) => {
+                Some(vec![("version".to_owned(), self.text(&value).to_owned())])
+            }
+            Node::Table(meta) => Some(
+                meta.entries()
+                    .read()
+                    .iter()
+                    .map(|(key, value)| (key.value().to_owned(), self.text(value).to_owned()))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    fn insert_workspace_dependencies(&self, lines: &[String]) -> Option<(Range<usize>, String)> {
+        let path = self.path();
+        let workspace = self.workspace()?;
+        let lines = lines.join("\n");
+
+        let Some(dependencies) = get_table(&workspace, &["workspace"], "dependencies", &path)
+        else {
+            let separator = if self.toml.ends_with('\n') { "" } else { "\n" };
+            let end = self.toml.len();
+            return Some((
+                end..end,
+                format!("{separator}\n[workspace.dependencies]\n{lines}\n"),
+            ));
+        };
+
+        if dependencies.kind() != TableKind::Regular {
+            log::warn!("{path}: workspace.dependencies must be a [table] to hoist into it");
+            return None;
+        }
+
+        let last = dependencies
+            .entries()
+            .read()
+            .iter()
+            .filter_map(|(_, value)| value.text_ranges(true).next())
+            .map(|range| usize::from(range.end()))
+            .max();
+        let after = match last {
+            Some(last) => last,
+            None => usize::from(dependencies.syntax()?.text_range().end()),
+        };
+        let end = self.toml[after..]
+            .find('\n')
+            .map_or(self.toml.len(), |offset| after + offset);
+
+        Some((end..end, format!("\n{lines}")))
+    }
+}
+
+impl Entry {
+    fn normalized(pairs: &[(String, String)]) -> Vec<(&str, String)> {
+        let mut pairs = pairs
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.replace('\'', "\"")))
+            .collect::<Vec<_>>();
+        pairs.sort();
+        pairs
+    }
+
+    fn workspace_line(&self) -> String {
+        if let [(key, version)] = self.hoisted.as_slice()
+            && key == "version"
+        {
+            return format!("{} = {version}", self.raw_key);
+        }
+
+        format!("{} = {{ {} }}", self.raw_key, join_pairs(&self.hoisted))
+    }
+
+    fn member_edit(&self) -> (Range<usize>, String) {
+        let with = match self.style {
+            Style::Table => {
+                let mut body = String::from("\nworkspace = true");
+                for (key, value) in &self.kept {
+                    write!(body, "\n{key} = {value}").unwrap();
+                }
+                body
+            }
+            Style::String | Style::Inline if self.kept.is_empty() => {
+                format!("{}.workspace = true", self.raw_key)
+            }
+            Style::String | Style::Inline => {
+                let kept = join_pairs(&self.kept);
+                format!("{} = {{ workspace = true, {kept} }}", self.raw_key)
+            }
+        };
+
+        (self.range.clone(), with)
+    }
+}
+
+fn join_pairs(pairs: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (i, (key, value)) in pairs.iter().enumerate() {
+        if i != 0 {
+            out.push_str(", ");
+        }
+        write!(out, "{key} = {value}").unwrap();
+    }
+    out
+}
+
+/// Finds the workspace root that `member` belongs to, if any.
+fn find_workspace(manifests: &[Manifest], member: usize) -> Option<usize> {
+    let dir = &manifests[member].dir;
+
+    manifests
+        .iter()
+        .enumerate()
+        .filter(|(_, manifest)| manifest.includes(dir))
+        .max_by_key(|(_, manifest)| manifest.dir.as_str().len())
+        .map(|(id, _)| id)
+}
+
+fn find_candidates(manifests: &[Manifest], entries: &[Entry]) -> Vec<Candidate> {
+    let mut groups: HashMap<(usize, &str), Vec<usize>> = HashMap::new();
+    for (id, entry) in entries.iter().enumerate() {
+        groups
+            .entry((entry.workspace, &entry.key))
+            .or_default()
+            .push(id);
+    }
+
+    let mut candidates = Vec::new();
+    for ((workspace, key), ids) in groups {
+        let workspace_path = manifests[workspace].path();
+
+        if let Some(existing) = manifests[workspace].workspace_dependency(key) {
+            let existing = Entry::normalized(&existing);
+            let ids = ids
+                .into_iter()
+                .filter(|id| Entry::normalized(&entries[*id].hoisted) == existing)
+                .collect::<Vec<_>>();
+
+            if !ids.is_empty() {
+                candidates.push(Candidate {
+                    workspace,
+                    existing: true,
+                    entries: ids,
+                });
+            }
+            continue;
+        }
+
+        let first = Entry::normalized(&entries[ids[0]].hoisted);
+        if ids
+            .iter()
+            .any(|id| Entry::normalized(&entries[*id].hoisted) != first)
+        {
+            log::warn!(
+                "{workspace_path}: members disagree on how to depend on {key}, not hoisting"
+            );
+            continue;
+        }
+
+        let mut members = ids
+            .iter()
+            .map(|id| entries[*id].manifest)
+            .collect::<Vec<_>>();
+        members.dedup();
+        if members.len() > 1 {
+            candidates.push(Candidate {
+                workspace,
+                existing: false,
+                entries: ids,
+            });
+        }
+    }
+
+    candidates.sort_by_cached_key(|candidate| {
+        (
+            manifests[candidate.workspace].dir.clone(),
+            entries[candidate.entries[0]].key.clone(),
+        )
+    });
+    candidates
+}
+
+struct DisplayCandidate<'a> {
+    manifests: &'a [Manifest],
+    entries: &'a [Entry],
+    candidate: &'a Candidate,
+}
+
+impl fmt::Display for DisplayCandidate<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let first = &self.entries[self.candidate.entries[0]];
+        write!(f, "{} ({})", first.key, join_pairs(&first.hoisted))?;
+
+        if self.candidate.existing {
+            f.write_str(", already in workspace")?;
+        }
+
+        f.write_str(": ")?;
+        let mut members = self
+            .candidate
+            .entries
+            .iter()
+            .map(|id| {
+                let dir = &self.manifests[self.entries[*id].manifest].dir;
+                if dir == "" { "." } else { dir.as_str() }
+            })
+            .collect::<Vec<_>>();
+        members.dedup();
+        f.write_str(&members.join(", "))
+    }
+}
@@ -0,0 +1,256 @@
+//! Resolves which index to query for a dependency, following Cargo's
+//! `[registries]`, `[source]` replacement, and credential configuration.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::Read as _;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use taplo::dom::Node;
+
+static CRATES_IO: &str = "crates-io";
+static CRATES_IO_INDEX: &str = "sparse+https://index.crates.io/";
+
+#[derive(Default)]
+pub(in crate::managers) struct Registries {
+    /// `[registries.<name>] index`
+    indexes: HashMap<String, String>,
+    /// `[source.<name>]`
+    sources: HashMap<String, Source>,
+    tokens: HashMap<String, String>,
+}
+
+#[derive(Default)]
+struct Source {
+    replace_with: Option<String>,
+    registry: Option<String>,
+    local: bool,
+}
+
+pub(in crate::managers) enum Index {
+    Sparse { url: String, token: Option<String> },
+    Git { url: String },
+}
+
+impl Registries {
+    /// Loads configuration from `.cargo/config.toml` in the current directory
+    /// and its parents, then from `$CARGO_HOME`.
+    pub(in crate::managers) fn load() -> Self {
+        let mut registries = Self::default();
+
+        let cwd = env::current_dir().unwrap();
+        let cwd = Utf8PathBuf::try_from(cwd).unwrap();
+        let home = cargo_home();
+
+        // Closer files take precedence, so load them last
+        let mut dirs = cwd
+            .ancestors()
+            .map(|dir| dir.join(".cargo"))
+            .filter(|dir| Some(dir) != home.as_ref())
+            .collect::<Vec<_>>();
+        dirs.extend(home.clone());
+        for dir in dirs.iter().rev() {
+            for name in ["config", "config.toml"] {
+                registries.load_file(&dir.join(name));
+            }
+        }
+
+        if let Some(home) = home {
+            for name in ["credentials", "credentials.toml"] {
+                registries.load_file(&home.join(name));
+            }
+        }
+
+        for (key, value) in env::vars() {
+            let Some(key) = key.strip_prefix("CARGO_REGISTRIES_") else {
+                if key == "CARGO_REGISTRY_TOKEN" {
+                    registries.tokens.insert(CRATES_IO.to_owned(), value);
+                }
+                continue;
+            };
+
+            // Environment variables can't tell `-` and `_` apart
+            let name = |suffix| {
+                let name = key.strip_suffix(suffix)?.to_lowercase();
+                let dashed = name.replace('_', "-");
+                Some(if registries.indexes.contains_key(&dashed) {
+                    dashed
+                } else {
+                    name
+                })
+            };
+
+            if let Some(name) = name("_TOKEN") {
+                registries.tokens.insert(name, value);
+            } else if let Some(name) = name("_INDEX") {
+                registries.indexes.insert(name, value);
+            }
+        }
+
+        registries
+    }
+
+    fn load_file(&mut self, path: &Utf8Path) {
+        let Ok(toml) = fs::read_to_string(path) else {
+            return;
+        };
+        log::debug!("reading Cargo config from {path}");
+
+        let dom = taplo::parser::parse(&toml).into_dom();
+        let Some(root) = dom.as_table() else {
+            return;
+        };
+
+        let get_str = |node: &Node, key: &str| {
+            let node = node.as_table()?.get(key)?;
+            Some(node.as_str()?.value().to_owned())
+        };
+
+        if let Some(Node::Table(registries)) = root.get("registries") {
+            for (name, registry) in registries.entries().read().iter() {
+                let name = name.value().to_owned();
+                if let Some(index) = get_str(registry, "index") {
+                    self.indexes.insert(name.clone(), index);
+                }
+                if let Some(token) = get_str(registry, "token") {
+                    self.tokens.insert(name, token);
+                }
+            }
+        }
+
+        if let Some(registry) = root.get("registry")
+            && let Some(token) = get_str(&registry, "token")
+        {
+            self.tokens.insert(CRATES_IO.to_owned(), token);
+        }
+
+        if let Some(Node::Table(sources)) = root.get("source") {
+            for (name, source) in sources.entries().read().iter() {
+                let local = ["directory", "local-registry"]
+                    .into_iter()
+                    .any(|key| get_str(source, key).is_some());
+
+                self.sources.insert(
+                    name.value().to_owned(),
+                    Source {
+                        replace_with: get_str(source, "replace-with"),
+                        registry: get_str(source, "registry"),
+                        local,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Resolves the `registry` a dependency was declared with, either a
+    /// registry name or an index URL from `registry-index`.
+    pub(in crate::managers) fn resolve(&self, registry: Option<&str>) -> Option<Index> {
+        let registry = registry.unwrap_or(CRATES_IO);
+        if registry.contains(':') {
+            return Some(Index::new(registry, None));
+        }
+
+        let mut name = registry;
+        for _ in 0..self.sources.len() + 1 {
+            let Some(source) = self.sources.get(name) else {
+                break;
+            };
+
+            if let Some(replacement) = source.replace_with.as_deref() {
+                log::debug!("{name} is replaced with {replacement}");
+                name = replacement;
+            } else if let Some(url) = source.registry.as_deref() {
+                return Some(Index::new(url, self.tokens.get(name)));
+            } else if source.local {
+                log::warn!("cannot check for updates in local source {name}");
+                return None;
+            } else {
+                break;
+            }
+        }
+
+        if let Some(url) = self.indexes.get(name) {
+            Some(Index::new(url, self.tokens.get(name)))
+        } else if name == CRATES_IO {
+            Some(Index::new(CRATES_IO_INDEX, self.tokens.get(name)))
+        } else {
+            log::warn!("unknown registry {name}");
+            None
+        }
+    }
+}
+
+impl Index {
+    fn new(url: &str, token: Option<&String>) -> Self {
+        if let Some(url) = url.strip_prefix("sparse+") {
+            Self::Sparse {
+                url: url.trim_end_matches('/').to_owned(),
+                token: token.cloned(),
+            }
+        } else {
+            let url = url.strip_prefix("registry+").unwrap_or(url);
+            Self::Git {
+                url: url.to_owned(),
+            }
+        }
+    }
+
+    /// Fetches the index file for the crate `name`.
+    pub(in crate::managers) fn fetch(&self, name: &str) -> Option<String> {
+        let path = index_path(name);
+
+        match self {
+            Self::Sparse { url, token } => {
+                let mut request = ureq::get(format!("{url}/{path}"));
+                if let Some(token) = token {
+                    request = request.header("Authorization", token);
+                }
+
+                let mut response = match request.call() {
+                    Ok(response) => response,
+                    Err(err) => {
+                        log::warn!("failed to fetch {name} from {url}: {err}");
+                        return None;
+                    }
+                };
+
+                let mut body = String::new();
+                response
+                    .body_mut()
+                    .as_reader()
+                    .read_to_string(&mut body)
+                    .ok()?;
+                Some(body)
+            }
+            Self::Git { url } => {
+                let repo = crate::git::open_repo(url);
+                let Some(file) = crate::git::read_file(&repo, &path) else {
+                    log::warn!("could not find {name} in {url}");
+                    return None;
+                };
+                String::from_utf8(file).ok()
+            }
+        }
+    }
+}
+
+fn cargo_home() -> Option<Utf8PathBuf> {
+    let home = env::var_os("CARGO_HOME")
+        .map(Into::into)
+        .or_else(|| Some(dirs::home_dir()?.join(".cargo")))?;
+    Utf8PathBuf::from_path_buf(home).ok()
+}
+
+/// Path of a crate's file within an index.
+fn index_path(name: &str) -> String {
+    // NOTE: assumes name is ascii
+    let name = name.to_lowercase();
+    match name.len() {
+        0 => unreachable!(),
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[0..1]),
+        _ => format!("{}/{}/{name}", &name[0..2], &name[2..4]),
+    }
+}
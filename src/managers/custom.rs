@@ -0,0 +1,249 @@
+pub(super) mod datasource;
+
+use std::collections::HashSet;
+use std::fs;
+
+use anyhow::Context as _;
+use camino::Utf8Path;
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+
+use crate::DepCollector;
+use crate::config::{self, Datasource};
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+
+static KIND_PREFIX: &str = "regex\0";
+/// Prefix of the protocol recording which rule found a dependency
+static RULE_PROTOCOL: &str = "regex:";
+
+pub(super) struct Manager {
+    rules: Vec<Rule>,
+    sources: datasource::Sources,
+}
+
+struct Rule {
+    glob: String,
+    files: GlobMatcher,
+    pattern: Regex,
+    dep_name: Option<String>,
+    datasource: Datasource,
+    registry: Option<String>,
+}
+
+impl Manager {
+    pub(super) fn new(rules: &[config::RegexRule]) -> anyhow::Result<Self> {
+        let rules = rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                let context = || format!("regex rule {}", i + 1);
+                let files = GlobBuilder::new(&rule.files)
+                    .literal_separator(true)
+                    .build()
+                    .with_context(context)?
+                    .compile_matcher();
+                let pattern = Regex::new(&rule.pattern).with_context(context)?;
+
+                let groups = pattern.capture_names().flatten().collect::<HashSet<_>>();
+                if !groups.contains("version") {
+                    anyhow::bail!("{}: pattern has no `version` group", context());
+                }
+                if !groups.contains("name") && rule.dep_name.is_none() {
+                    anyhow::bail!(
+                        "{}: pattern has no `name` group and `dep-name` is not set",
+                        context()
+                    );
+                }
+
+                Ok(Rule {
+                    glob: rule.files.clone(),
+                    files,
+                    pattern,
+                    dep_name: rule.dep_name.clone(),
+                    datasource: rule.datasource,
+                    registry: rule.registry.clone(),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            rules,
+            sources: datasource::Sources::default(),
+        })
+    }
+}
+
+impl Rule {
+    /// Finds every match in `text`, returning the dependency name and where
+    /// its version is.
+    fn matches<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (&'a str, regex::Match<'a>)> {
+        self.pattern.captures_iter(text).filter_map(|captures| {
+            let name = match captures.name("name") {
+                Some(name) => name.as_str(),
+                None => self.dep_name.as_deref()?,
+            };
+            Some((name, captures.name("version")?))
+        })
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "Regex"
+    }
+
+    fn interest(&self) -> super::Interest {
+        let globs = self.rules.iter().map(|rule| rule.glob.as_str());
+        super::Interest::files(&globs.collect::<Vec<_>>())
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        // Rules can match any file, so track the file itself
+        let path_id = collector.push_path(path.to_owned());
+        let text = fs::read_to_string(path).unwrap();
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            if !rule.files.is_match(path) {
+                continue;
+            }
+
+            let kind_id = collector.get_kind_id(format!("{KIND_PREFIX}{i}"), || {
+                format!("Regex ({})", rule.glob)
+            });
+
+            // Applying an update rewrites every match with the same version
+            let mut seen = HashSet::new();
+            for (name, version) in rule.matches(&text) {
+                if !seen.insert((name, version.as_str())) {
+                    continue;
+                }
+
+                collector.push_dep(DepInit {
+                    path: Some(path_id),
+                    kind: Some(kind_id),
+                    name: name.to_owned(),
+                    renamed: None,
+                    registry: None,
+                    protocol: Some(format!("{RULE_PROTOCOL}{i}")),
+                    version: Version::SemVer(version.as_str().to_owned()),
+                });
+            }
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        _notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let Version::SemVer(current) = &dep.version else {
+            unreachable!()
+        };
+
+        let rule = dep
+            .protocol
+            .as_deref()
+            .and_then(|protocol| protocol.strip_prefix(RULE_PROTOCOL))
+            .and_then(|rule| rule.parse::<usize>().ok())
+            .and_then(|rule| self.rules.get(rule));
+        let Some(rule) = rule else {
+            log::warn!("regex rule for {} no longer exists", dep.name);
+            return Updates::Failed;
+        };
+        let Some(versions) =
+            self.sources
+                .versions(rule.datasource, rule.registry.as_deref(), &dep.name)
+        else {
+            return Updates::Failed;
+        };
+
+        if let Some((prefix, ..)) = parse_version(current) {
+            let releases = versions
+                .iter()
+                .filter_map(|version| Some((with_prefix(prefix, version)?, None)));
+            *candidates = super::candidates(current, releases, Version::SemVer);
+        }
+
+        match newest(current, &versions) {
+            Some(latest) => Updates::Found(Version::SemVer(latest)),
+            None => Updates::None,
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let (Version::SemVer(current), Version::SemVer(latest)) = (&dep.version, version) else {
+            unreachable!()
+        };
+
+        let path = deps.path(dep.path.unwrap());
+        let mut text = fs::read_to_string(path).unwrap();
+
+        let kind = deps.internal_kind(dep.kind.unwrap());
+        let rule = kind
+            .strip_prefix(KIND_PREFIX)
+            .unwrap()
+            .parse::<usize>()
+            .unwrap();
+        let Some(rule) = self.rules.get(rule) else {
+            log::warn!("{path}: regex rule for {} no longer exists", dep.name);
+            return;
+        };
+
+        let ranges = rule
+            .matches(&text)
+            .filter(|(name, version)| *name == dep.name && version.as_str() == current)
+            .map(|(_, version)| version.range())
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            log::warn!("{path}: could not find {} {current}", dep.name);
+            return;
+        }
+
+        for range in ranges.into_iter().rev() {
+            text.replace_range(range, latest);
+        }
+        fs::write(path, text).unwrap();
+    }
+}
+
+/// Splits a version or tag like `v1.2.3-rc1` into whatever precedes the
+/// numbers, the numbers, and whether anything follows them.
+fn parse_version(version: &str) -> Option<(&str, Vec<u64>, bool)> {
+    let start = version.find(|c: char| c.is_ascii_digit())?;
+    let rest = &version[start..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    let numbers = rest[..end]
+        .trim_end_matches('.')
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((&version[..start], numbers, end < rest.len()))
+}
+
+/// Finds the newest of `versions` past `current`, skipping pre-releases
+/// unless `current` is one and writing it with the prefix `current` has, eg
+/// `25.1` for the tag `v25.1`.
+fn newest(current: &str, versions: &[String]) -> Option<String> {
+    let (prefix, current_numbers, current_pre) = parse_version(current)?;
+
+    let (_, _, candidate) = versions
+        .iter()
+        .filter_map(|version| {
+            let (_, numbers, pre) = parse_version(version)?;
+            Some((numbers, pre, version))
+        })
+        .filter(|(numbers, pre, _)| (current_pre || !pre) && *numbers > current_numbers)
+        .max_by(|(a, _, _), (b, _, _)| a.cmp(b))?;
+
+    with_prefix(prefix, candidate)
+}
+
+/// Writes `version` with `prefix` in place of its own, eg `v25.1` for `25.1`
+/// and the prefix `v`.
+fn with_prefix(prefix: &str, version: &str) -> Option<String> {
+    let (own, ..) = parse_version(version)?;
+    Some(format!("{prefix}{}", &version[own.len()..]))
+}
@@ -0,0 +1,102 @@
+//! Where regex rules look up the versions available for a dependency.
+
+use std::env;
+use std::sync::OnceLock;
+
+use facet::Facet;
+
+use crate::config::Datasource;
+use crate::managers::cargo::registry::Registries;
+use crate::managers::package_json::npmrc::Npmrc;
+
+static GITHUB_API: &str = "https://api.github.com";
+
+#[derive(Default)]
+pub(in crate::managers) struct Sources {
+    registries: OnceLock<Registries>,
+    npmrc: OnceLock<Npmrc>,
+}
+
+#[derive(Facet)]
+struct Crate {
+    vers: String,
+    #[facet(default)]
+    yanked: bool,
+}
+
+#[derive(Facet)]
+struct NpmVersion {
+    version: String,
+}
+
+impl Sources {
+    /// Lists the versions of `name` that could replace the current one.
+    pub(in crate::managers) fn versions(
+        &self,
+        datasource: Datasource,
+        registry: Option<&str>,
+        name: &str,
+    ) -> Option<Vec<String>> {
+        match datasource {
+            Datasource::CratesIo => {
+                let registries = self.registries.get_or_init(Registries::load);
+                let index = registries.resolve(registry)?.fetch(name)?;
+                let versions = index
+                    .lines()
+                    .filter_map(|line| facet_json::from_str::<Crate>(line).ok())
+                    .filter(|release| !release.yanked)
+                    .map(|release| release.vers)
+                    .collect();
+                Some(versions)
+            }
+            Datasource::Npm => {
+                let npmrc = self.npmrc.get_or_init(Npmrc::load);
+                let data = match npmrc.get(name, "/latest") {
+                    Ok(data) => data,
+                    Err(err) => {
+                        log::warn!("failed to fetch {name}: {err}");
+                        return None;
+                    }
+                };
+                let NpmVersion { version } = facet_json::from_slice(&data).ok()?;
+                Some(vec![version])
+            }
+            Datasource::GitTags => {
+                let url = if name.contains("://") || name.starts_with(['/', '.']) {
+                    name.to_owned()
+                } else if let Some(base) = registry {
+                    format!("{}/{name}", base.trim_end_matches('/'))
+                } else {
+                    crate::git::git_url(name).into_owned()
+                };
+                let repo = crate::git::open_repo(&url);
+                Some(crate::git::tags(&repo))
+            }
+            Datasource::GithubReleases => {
+                let api = registry.unwrap_or(GITHUB_API).trim_end_matches('/');
+                let url = format!("{api}/repos/{name}/releases?per_page=100");
+                let mut request = ureq::get(&url).header("Accept", "application/vnd.github+json");
+                if let Ok(token) = env::var("GITHUB_TOKEN") {
+                    request = request.header("Authorization", format!("Bearer {token}"));
+                }
+
+                let body = match request.call() {
+                    Ok(mut response) => response.body_mut().read_to_string().ok()?,
+                    Err(err) => {
+                        log::warn!("failed to fetch releases of {name} from {api}: {err}");
+                        return None;
+                    }
+                };
+
+                let releases: serde_json::Value = serde_json::from_str(&body).ok()?;
+                let versions = releases
+                    .as_array()?
+                    .iter()
+                    .filter(|release| release["draft"] != true && release["prerelease"] != true)
+                    .filter_map(|release| Some(release["tag_name"].as_str()?.to_owned()))
+                    .collect();
+                Some(versions)
+            }
+        }
+    }
+}
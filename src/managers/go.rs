@@ -0,0 +1,374 @@
+mod modfile;
+pub(super) mod proxy;
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fs;
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::DepCollector;
+use crate::config;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+
+static GO_MOD: &str = "go.mod";
+/// Name of the dependency for the `toolchain` directive
+static TOOLCHAIN: &str = "go";
+/// Module whose versions are Go releases, like `v0.0.1-go1.22.3.linux-amd64`
+static TOOLCHAIN_MODULE: &str = "golang.org/toolchain";
+
+pub(super) struct Manager {
+    proxy: Option<proxy::Proxy>,
+    tidy: bool,
+}
+
+impl Manager {
+    pub(super) fn new(config: &config::Go) -> Self {
+        Self {
+            proxy: proxy::Proxy::new(config.proxy.as_deref()),
+            tidy: config.tidy,
+        }
+    }
+}
+
+fn latest_toolchain(releases: &[String]) -> Option<&String> {
+    releases
+        .iter()
+        .filter(|release| !is_go_prerelease(release))
+        .max_by(|a, b| compare_go(a, b))
+}
+
+/// Lists the Go releases `proxy` has toolchains for, like `go1.22.3`.
+pub(super) fn releases(proxy: &proxy::Proxy) -> Option<Vec<String>> {
+    let releases = proxy
+        .versions(TOOLCHAIN_MODULE)?
+        .iter()
+        .filter_map(|version| {
+            let release = version.split_once("-go")?.1;
+            let (release, _platform) = release.rsplit_once('.')?;
+            Some(format!("go{release}"))
+        })
+        .collect::<BTreeSet<_>>();
+    Some(releases.into_iter().collect())
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "Go"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(&["**/go.mod"])
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        let path_id = collector.push_path(path.parent().unwrap().into());
+        let text = fs::read_to_string(path).unwrap();
+
+        let push = |kind: &str, title: &str, name: &str, renamed: Option<&str>, version: &str| {
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind: Some(collector.get_kind_id(kind.to_owned(), || title.to_owned())),
+                name: name.to_owned(),
+                renamed: renamed.map(str::to_owned),
+                registry: None,
+                protocol: None,
+                version: Version::SemVer(version.to_owned()),
+            });
+        };
+
+        for directive in modfile::parse(&text) {
+            let args = directive
+                .args
+                .iter()
+                .map(|(arg, _)| *arg)
+                .collect::<Vec<_>>();
+            match (directive.verb, args.as_slice()) {
+                ("toolchain", [version]) => {
+                    push("toolchain", "Toolchain", TOOLCHAIN, None, version);
+                }
+                ("require", [module, version]) if directive.indirect => {
+                    push("indirect", "Indirect", module, None, version);
+                }
+                ("require", [module, version]) => push("require", "Runtime", module, None, version),
+                ("replace", [old, .., "=>", new, version]) if !is_local(new) => {
+                    push("replace", "Replace", new, Some(old), version);
+                }
+                ("replace", _) => log::debug!("{path}: skipping local replace {args:?}"),
+                ("require" | "toolchain", _) => log::warn!("{path}: invalid {args:?}"),
+                _ => {}
+            }
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let Version::SemVer(current) = &dep.version else {
+            unreachable!()
+        };
+        let Some(proxy) = &self.proxy else {
+            return Updates::Failed;
+        };
+
+        if dep.name == TOOLCHAIN {
+            let Some(releases) = releases(proxy) else {
+                return Updates::Failed;
+            };
+            let dated = releases.iter().map(|release| (release.clone(), None));
+            *candidates = super::candidates(current, dated, Version::SemVer);
+            return match latest_toolchain(&releases) {
+                Some(latest) if compare_go(latest, current).is_gt() => {
+                    Updates::Found(Version::SemVer(latest.clone()))
+                }
+                Some(_) => Updates::None,
+                None => Updates::Failed,
+            };
+        }
+
+        let Some(current_version) = SemVer::parse(current) else {
+            log::warn!("could not parse version {current} of {}", dep.name);
+            return Updates::Failed;
+        };
+        let Some(versions) = proxy.versions(&dep.name) else {
+            log::warn!("could not find {} in the Go proxy", dep.name);
+            return Updates::Failed;
+        };
+
+        // Without a `/vN` suffix, majors past v1 are only available as
+        // `+incompatible`, so stay on whichever side the module already is
+        let latest = versions
+            .iter()
+            .filter_map(|version| Some((version, SemVer::parse(version)?)))
+            .filter(|(_, version)| version.incompatible == current_version.incompatible)
+            .filter(|(_, version)| current_version.pre.is_some() || version.pre.is_none())
+            .max_by(|(_, a), (_, b)| a.cmp(b));
+
+        if !current_version.incompatible
+            && let Some(next) = next_major_path(&dep.name, current_version.major)
+            && proxy
+                .versions(&next)
+                .is_some_and(|versions| !versions.is_empty())
+        {
+            notes.push(format!("a new major version is available as `{next}`"));
+        }
+
+        // The proxy lists no times, and `+incompatible` versions are left
+        // out like pre-releases
+        let dated = versions.iter().map(|version| (version.clone(), None));
+        *candidates = super::candidates(current, dated, Version::SemVer);
+
+        match latest {
+            Some((latest, version)) if version > current_version => {
+                Updates::Found(Version::SemVer(latest.clone()))
+            }
+            _ => Updates::None,
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let (Version::SemVer(current), Version::SemVer(latest)) = (&dep.version, version) else {
+            unreachable!()
+        };
+
+        let path = deps.path(dep.path.unwrap()).join(GO_MOD);
+        let mut text = fs::read_to_string(&path).unwrap();
+        let kind = deps.internal_kind(dep.kind.unwrap());
+
+        let mut replacements = Vec::new();
+        for directive in modfile::parse(&text) {
+            let args = &directive.args;
+            let found = match (kind, directive.verb, args.as_slice()) {
+                ("toolchain", "toolchain", [version]) => Some(version),
+                ("require" | "indirect", "require", [(module, _), version]) => {
+                    (*module == dep.name).then_some(version)
+                }
+                ("replace", "replace", [(old, _), .., ("=>", _), (new, _), version]) => {
+                    (Some(*old) == dep.renamed.as_deref() && *new == dep.name).then_some(version)
+                }
+                _ => None,
+            };
+
+            if let Some(&(version, offset)) = found
+                && version == current
+            {
+                replacements.push(offset..offset + version.len());
+            }
+        }
+
+        if replacements.is_empty() {
+            log::warn!("{path}: could not find {} {current}", dep.name);
+            return;
+        }
+
+        for range in replacements.into_iter().rev() {
+            text.replace_range(range, latest);
+        }
+        fs::write(path, text).unwrap();
+    }
+
+    fn after_apply(&self, deps: &Deps, applied: &[&Dep]) {
+        if !self.tidy {
+            return;
+        }
+
+        let modules = applied
+            .iter()
+            .map(|dep| deps.path(dep.path.unwrap()))
+            .collect::<BTreeSet<_>>();
+        for module in modules {
+            log::info!("running go mod tidy in {module}");
+            let result = duct::cmd!("go", "mod", "tidy")
+                .dir(module)
+                .stdin_null()
+                .run();
+            if let Err(err) = result {
+                log::warn!("go mod tidy failed in {module}: {err}");
+            }
+        }
+    }
+
+    fn manifest(&self, _deps: &Deps, path: &Utf8Path, _kind: Option<usize>) -> Utf8PathBuf {
+        path.join(GO_MOD)
+    }
+}
+
+fn is_local(path: &str) -> bool {
+    path.starts_with("./") || path.starts_with("../") || path.starts_with('/')
+}
+
+/// Module path for the next major version, eg `example.com/foo/v3` after
+/// `example.com/foo/v2` or `example.com/foo/v2` after `example.com/foo`.
+fn next_major_path(module: &str, major: u64) -> Option<String> {
+    // gopkg.in encodes the major version as `.vN` instead
+    if module.starts_with("gopkg.in/") {
+        return None;
+    }
+
+    match module.rsplit_once('/') {
+        Some((base, suffix))
+            if suffix
+                .strip_prefix('v')
+                .is_some_and(|n| n.parse::<u64>().is_ok_and(|n| n >= 2)) =>
+        {
+            Some(format!("{base}/v{}", major + 1))
+        }
+        _ if major <= 1 => Some(format!("{module}/v2")),
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SemVer<'a> {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    /// Pre-release, including pseudo-versions like `0.20240101000000-abcdef`
+    pre: Option<&'a str>,
+    incompatible: bool,
+}
+
+impl<'a> SemVer<'a> {
+    fn parse(version: &'a str) -> Option<Self> {
+        let version = version.strip_prefix('v')?;
+        let (version, incompatible) = match version.strip_suffix("+incompatible") {
+            Some(version) => (version, true),
+            None => (version, false),
+        };
+        let version = version.split('+').next().unwrap();
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+
+        let mut parts = core.split('.').map(|part| part.parse().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next()??;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            major,
+            minor,
+            patch,
+            pre,
+            incompatible,
+        })
+    }
+}
+
+impl Ord for SemVer<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let compare_pre = |a: &str, b: &str| {
+            let mut a = a.split('.');
+            let mut b = b.split('.');
+            loop {
+                let ordering = match (a.next(), b.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                        (Ok(a), Ok(b)) => a.cmp(&b),
+                        (Ok(_), Err(_)) => Ordering::Less,
+                        (Err(_), Ok(_)) => Ordering::Greater,
+                        (Err(_), Err(_)) => a.cmp(b),
+                    },
+                };
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+        };
+
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre, other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_pre(a, b),
+            })
+    }
+}
+
+impl PartialOrd for SemVer<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Splits a Go release like `go1.22rc1` into its numbers and any pre-release
+/// suffix.
+fn split_go(release: &str) -> (Vec<u64>, &str) {
+    let release = release.strip_prefix("go").unwrap_or(release);
+    let end = release
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(release.len());
+    let numbers = release[..end]
+        .split('.')
+        .filter_map(|part| part.parse().ok())
+        .collect();
+    (numbers, &release[end..])
+}
+
+fn is_go_prerelease(release: &str) -> bool {
+    !split_go(release).1.is_empty()
+}
+
+fn compare_go(a: &str, b: &str) -> Ordering {
+    let (a_numbers, a_pre) = split_go(a);
+    let (b_numbers, b_pre) = split_go(b);
+    a_numbers
+        .cmp(&b_numbers)
+        .then_with(|| match (a_pre.is_empty(), b_pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => a_pre.cmp(b_pre),
+        })
+}
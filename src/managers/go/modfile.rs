@@ -0,0 +1,66 @@
+//! Line-based parsing of `go.mod`, keeping the position of every token so
+//! versions can be rewritten in place.
+
+pub(super) struct Directive<'a> {
+    pub(super) verb: &'a str,
+    /// Arguments and their byte offsets in the file
+    pub(super) args: Vec<(&'a str, usize)>,
+    /// Whether the line has an `// indirect` comment
+    pub(super) indirect: bool,
+}
+
+pub(super) fn parse(text: &str) -> Vec<Directive<'_>> {
+    let mut directives = Vec::new();
+    let mut block = None;
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+
+        let (code, comment) = line.split_once("//").unwrap_or((line, ""));
+        let indirect = comment.trim() == "indirect" || comment.trim().starts_with("indirect;");
+        let tokens = tokens(code, line_offset);
+
+        match (block, tokens.as_slice()) {
+            (_, []) => {}
+            (Some(_), [(")", _)]) => block = None,
+            (Some(verb), _) => directives.push(Directive {
+                verb,
+                args: tokens,
+                indirect,
+            }),
+            (None, [(verb, _), ("(", _)]) => block = Some(*verb),
+            (None, [(verb, _), args @ ..]) => directives.push(Directive {
+                verb,
+                args: args.to_vec(),
+                indirect,
+            }),
+        }
+    }
+
+    directives
+}
+
+fn tokens(code: &str, offset: usize) -> Vec<(&str, usize)> {
+    let mut tokens = Vec::new();
+    let mut rest = code;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        let token = &rest[start..];
+        let len = token.find(char::is_whitespace).unwrap_or(token.len());
+        let position = offset + (code.len() - rest.len()) + start;
+        tokens.push((
+            unquote(&token[..len]),
+            position + usize::from(token.starts_with('"')),
+        ));
+        rest = &token[len..];
+    }
+    tokens
+}
+
+fn unquote(token: &str) -> &str {
+    token
+        .strip_prefix('"')
+        .and_then(|token| token.strip_suffix('"'))
+        .unwrap_or(token)
+}
@@ -0,0 +1,86 @@
+//! Client for the GOPROXY protocol, over HTTP or from a `file://` directory.
+
+use std::env;
+use std::fs;
+
+use camino::Utf8PathBuf;
+
+static DEFAULT_PROXY: &str = "https://proxy.golang.org";
+
+pub(in crate::managers) enum Proxy {
+    Http(String),
+    File(Utf8PathBuf),
+}
+
+impl Proxy {
+    /// Uses the configured proxy, then the first usable entry of `GOPROXY`.
+    pub(in crate::managers) fn new(configured: Option<&str>) -> Option<Self> {
+        let list = configured
+            .map(str::to_owned)
+            .or_else(|| env::var("GOPROXY").ok())
+            .unwrap_or_else(|| DEFAULT_PROXY.to_owned());
+
+        let Some(url) = list
+            .split([',', '|'])
+            .map(str::trim)
+            .find(|url| !matches!(*url, "" | "direct" | "off"))
+        else {
+            log::warn!("GOPROXY {list:?} has no proxy to query");
+            return None;
+        };
+
+        Some(match url.strip_prefix("file://") {
+            Some(path) => Self::File(path.into()),
+            None => Self::Http(url.trim_end_matches('/').to_owned()),
+        })
+    }
+
+    /// Lists the tagged versions of `module`, falling back to `@latest` when
+    /// there are none.
+    pub(in crate::managers) fn versions(&self, module: &str) -> Option<Vec<String>> {
+        let escaped = escape(module);
+        let list = self.get(&format!("{escaped}/@v/list"))?;
+        let versions = list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        if !versions.is_empty() {
+            return Some(versions);
+        }
+
+        let latest = self.get(&format!("{escaped}/@latest"))?;
+        let latest: serde_json::Value = serde_json::from_str(&latest).ok()?;
+        Some(vec![latest["Version"].as_str()?.to_owned()])
+    }
+
+    fn get(&self, path: &str) -> Option<String> {
+        match self {
+            Self::Http(base) => match ureq::get(format!("{base}/{path}")).call() {
+                Ok(mut response) => response.body_mut().read_to_string().ok(),
+                Err(ureq::Error::StatusCode(404 | 410)) => None,
+                Err(err) => {
+                    log::warn!("failed to fetch {path} from {base}: {err}");
+                    None
+                }
+            },
+            Self::File(base) => fs::read_to_string(base.join(path)).ok(),
+        }
+    }
+}
+
+/// Escapes a module path for the proxy, which encodes uppercase letters as
+/// `!` followed by the lowercase letter.
+fn escape(module: &str) -> String {
+    let mut out = String::with_capacity(module.len());
+    for c in module.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('!');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+
+use camino::Utf8Path;
+use taplo::dom::Node;
+use taplo::dom::node::DomNode as _;
+
+use super::custom::datasource::Sources;
+use super::go::{self, proxy::Proxy};
+use super::package_json::DEFAULT_NODE_INDEX;
+use super::toolchain::dist;
+use crate::DepCollector;
+use crate::config::{self, Datasource};
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+
+/// The asdf format, which mise reads too
+static TOOL_VERSIONS: &str = ".tool-versions";
+static MISE_FILES: &[&str] = &[
+    "**/.tool-versions",
+    "**/mise.toml",
+    "**/.mise.toml",
+    "**/mise.local.toml",
+    "**/.mise.local.toml",
+];
+static DEFAULT_PYTHON_RELEASES: &str =
+    "https://www.python.org/api/v2/downloads/release/?is_published=true";
+
+pub(super) struct Manager {
+    sources: Sources,
+    dist: dist::Server,
+    proxy: Option<Proxy>,
+    node_index: String,
+    python_releases: String,
+}
+
+/// Where the releases of a tool are listed
+enum Source<'a> {
+    Node,
+    Rust,
+    Go,
+    Python,
+    /// A backend like `cargo:ripgrep`, looked up like regex rules do
+    Backend(Datasource, &'a str),
+}
+
+/// A tool pinned in one of the files, and where its version is
+struct Pin {
+    tool: String,
+    version: String,
+    span: Range<usize>,
+}
+
+impl Manager {
+    pub(super) fn new(config: &config::Config) -> Self {
+        Self {
+            sources: Sources::default(),
+            dist: dist::Server::new(config.rust.dist_server.as_deref()),
+            proxy: Proxy::new(config.go.proxy.as_deref()),
+            node_index: config
+                .npm
+                .node_index
+                .clone()
+                .unwrap_or_else(|| DEFAULT_NODE_INDEX.to_owned()),
+            python_releases: config
+                .mise
+                .python_releases
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PYTHON_RELEASES.to_owned()),
+        }
+    }
+
+    fn versions(&self, source: Source<'_>) -> Option<Vec<String>> {
+        match source {
+            Source::Node => {
+                let releases = fetch_json(&self.node_index)?;
+                let versions = releases
+                    .as_array()?
+                    .iter()
+                    .filter_map(|release| Some(release["version"].as_str()?.to_owned()))
+                    .collect();
+                Some(versions)
+            }
+            Source::Rust => Some(vec![self.dist.manifest("stable", None)?.version?]),
+            Source::Go => go::releases(self.proxy.as_ref()?),
+            Source::Python => {
+                let releases = fetch_json(&self.python_releases)?;
+                let versions = releases
+                    .as_array()?
+                    .iter()
+                    .filter(|release| release["pre_release"] != true)
+                    .filter_map(|release| Some(release["name"].as_str()?.to_owned()))
+                    .collect();
+                Some(versions)
+            }
+            Source::Backend(datasource, name) => self.sources.versions(datasource, None, name),
+        }
+    }
+}
+
+impl<'a> Source<'a> {
+    fn parse(tool: &'a str) -> Option<Self> {
+        let source = match tool.split_once(':') {
+            Some(("cargo", name)) => Self::Backend(Datasource::CratesIo, name),
+            Some(("npm", name)) => Self::Backend(Datasource::Npm, name),
+            // Options like `ubi:owner/repo[exe=name]` follow the repository
+            Some(("github" | "ubi" | "aqua", name)) => {
+                let repo = name.split('[').next().unwrap();
+                Self::Backend(Datasource::GithubReleases, repo)
+            }
+            Some(_) => return None,
+            None => match tool {
+                "node" | "nodejs" => Self::Node,
+                "rust" => Self::Rust,
+                "go" | "golang" => Self::Go,
+                "python" => Self::Python,
+                _ => return None,
+            },
+        };
+        Some(source)
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "mise"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(MISE_FILES)
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        let path_id = collector.push_path(path.to_owned());
+        let text = fs::read_to_string(path).unwrap();
+
+        let mut seen = HashSet::new();
+        for pin in pins(path, &text) {
+            if Source::parse(&pin.tool).is_none() {
+                log::debug!("{path}: skipping unsupported tool {}", pin.tool);
+                continue;
+            }
+            // Aliases like `lts`, `latest`, `ref:` and `path:` float or
+            // aren't releases
+            if super::split_version(&pin.version).is_none() {
+                log::debug!("{path}: skipping {} {}", pin.tool, pin.version);
+                continue;
+            }
+            if !seen.insert(pin.tool.clone()) {
+                continue;
+            }
+
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind: None,
+                name: pin.tool,
+                renamed: None,
+                registry: None,
+                protocol: None,
+                version: Version::SemVer(pin.version),
+            });
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        _notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let Version::SemVer(current) = &dep.version else {
+            unreachable!()
+        };
+
+        let Some(versions) = Source::parse(&dep.name).and_then(|source| self.versions(source))
+        else {
+            return Updates::Failed;
+        };
+        *candidates = super::newer(current, &versions);
+
+        match super::newest(current, &versions) {
+            Some(latest) => Updates::Found(Version::SemVer(latest)),
+            None => Updates::None,
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let (Version::SemVer(current), Version::SemVer(latest)) = (&dep.version, version) else {
+            unreachable!()
+        };
+
+        let path = deps.path(dep.path.unwrap());
+        let mut text = fs::read_to_string(path).unwrap();
+
+        let Some(pin) = pins(path, &text)
+            .into_iter()
+            .find(|pin| pin.tool == dep.name && pin.version == *current)
+        else {
+            log::warn!("{path}: could not find {} {current}", dep.name);
+            return;
+        };
+
+        text.replace_range(pin.span, latest);
+        fs::write(path, text).unwrap();
+    }
+}
+
+fn fetch_json(url: &str) -> Option<serde_json::Value> {
+    let data = match ureq::get(url).call() {
+        Ok(mut response) => response
+            .body_mut()
+            .with_config()
+            .limit(u64::MAX)
+            .read_to_vec()
+            .ok()?,
+        Err(err) => {
+            log::warn!("failed to fetch {url}: {err}");
+            return None;
+        }
+    };
+    serde_json::from_slice(&data).ok()
+}
+
+/// Finds the first version of each tool, as mise installs the others
+/// alongside it.
+fn pins(path: &Utf8Path, text: &str) -> Vec<Pin> {
+    if path.file_name() == Some(TOOL_VERSIONS) {
+        return tool_versions_pins(text);
+    }
+
+    let dom = taplo::parser::parse(text).into_dom();
+    let Some(tools) = dom.as_table().and_then(|root| root.get("tools")) else {
+        return Vec::new();
+    };
+    let Some(tools) = tools.as_table() else {
+        log::warn!("{path}: tools is not a table");
+        return Vec::new();
+    };
+
+    let mut pins = Vec::new();
+    for (tool, value) in tools.entries().read().iter() {
+        let version = match value {
+            Node::Array(array) => array.items().read().first().cloned(),
+            Node::Table(table) => table.get("version"),
+            value => Some(value.clone()),
+        };
+        let Some(version) = version.as_ref().and_then(Node::as_str) else {
+            continue;
+        };
+
+        // Strip the quotes
+        let range = version.syntax().unwrap().text_range();
+        let span = usize::from(range.start()) + 1..usize::from(range.end()) - 1;
+        pins.push(Pin {
+            tool: tool.value().to_owned(),
+            version: version.value().to_owned(),
+            span,
+        });
+    }
+    pins
+}
+
+/// Reads lines like `python 3.12.1 3.11.7 # comment`.
+fn tool_versions_pins(text: &str) -> Vec<Pin> {
+    let mut pins = Vec::new();
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+
+        let content = line.split('#').next().unwrap();
+        let mut tokens = content.split_whitespace();
+        let (Some(tool), Some(version)) = (tokens.next(), tokens.next()) else {
+            continue;
+        };
+
+        let start = line_offset + (version.as_ptr().addr() - line.as_ptr().addr());
+        pins.push(Pin {
+            tool: tool.to_owned(),
+            version: version.to_owned(),
+            span: start..start + version.len(),
+        });
+    }
+
+    pins
+}
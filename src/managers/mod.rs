@@ -1,31 +1,309 @@
-mod cargo;
+pub(crate) mod cargo;
+mod custom;
 mod galock;
-mod pnpm;
+mod go;
+mod mise;
+mod oci;
+mod package_json;
+mod plugin;
+mod pre_commit;
+mod python;
+mod submodule;
+mod terraform;
+mod toolchain;
 
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
+use globset::{Glob, GlobBuilder};
 
-use crate::dep_collector::{Dep, Deps, Updates, Version};
+use self::package_json::Tool;
+use crate::config::Config;
+use crate::dep_collector::{Candidate, Dep, Deps, Updates, Version};
+use crate::git;
 
-pub(crate) fn all() -> Vec<Box<dyn Manager>> {
-    vec![
-        Box::new(cargo::Manager),
+pub fn all(config: &Config) -> anyhow::Result<Vec<Box<dyn Manager>>> {
+    let mut managers: Vec<Box<dyn Manager>> = vec![
+        Box::new(cargo::Manager::default()),
         Box::new(galock::Manager),
-        Box::new(pnpm::Manager),
-    ]
+        Box::new(package_json::Manager::new(Tool::Npm, &config.npm)),
+        Box::new(package_json::Manager::new(Tool::Pnpm, &config.npm)),
+        Box::new(package_json::Manager::new(Tool::Yarn, &config.npm)),
+        Box::new(package_json::Manager::new(Tool::Bun, &config.npm)),
+        Box::new(python::Manager::new(&config.python)),
+        Box::new(go::Manager::new(&config.go)),
+        Box::new(toolchain::Manager::new(&config.rust)),
+        Box::new(oci::Manager::new(&config.oci)),
+        Box::new(submodule::Manager),
+        Box::new(pre_commit::Manager),
+        Box::new(mise::Manager::new(config)),
+        Box::new(terraform::Manager::new(&config.terraform)),
+        Box::new(custom::Manager::new(&config.regex)?),
+    ];
+
+    for plugin in plugin::all(&config.plugins) {
+        managers.push(Box::new(plugin));
+    }
+
+    Ok(managers)
 }
 
-pub(crate) trait Manager {
+/// Finds and updates the dependencies of one package format.
+///
+/// Paths are relative to the working directory.
+pub trait Manager {
+    /// Display name, shown in summaries and logs.
     fn name(&self) -> &'static str;
 
-    fn walk_directory(&self, path: &Utf8Path) -> bool {
-        path.file_name().is_none_or(|name| !name.starts_with('.'))
-    }
-
-    fn walk_file(&self, path: &Utf8Path) -> bool;
+    /// Which files to pass to [`scan_file`](Self::scan_file), and which
+    /// directories to look for them in. Read once before walking.
+    fn interest(&self) -> Interest;
 
+    /// Reports the dependencies declared in a file to `collector`.
     fn scan_file(&self, path: &Utf8Path, collector: crate::DepCollector<'_>);
 
-    fn find_updates(&self, dep: &Dep) -> Updates;
+    /// Looks up the version to update a dependency to, adding anything worth
+    /// mentioning about it to `notes`, and any newer versions to pick from
+    /// instead to `candidates`, newest first.
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates;
 
+    /// Rewrites a dependency to `version`.
     fn apply(&self, deps: &Deps, dep: &Dep, version: &Version);
+
+    /// Called once after applying updates, eg to refresh lockfiles.
+    fn after_apply(&self, _deps: &Deps, _applied: &[&Dep]) {}
+
+    /// The file declaring dependencies of `kind` recorded at `path`, for
+    /// managers recording the directory of their manifest instead.
+    fn manifest(&self, _deps: &Deps, path: &Utf8Path, _kind: Option<usize>) -> Utf8PathBuf {
+        path.to_owned()
+    }
+}
+
+/// Globs of the files and directories a [`Manager`] wants walked, matched
+/// against paths relative to the working directory. `*` does not match `/`.
+#[derive(Clone)]
+pub struct Interest {
+    pub files: Vec<Glob>,
+    /// A directory is only walked if it matches, and so did its parent
+    pub directories: Vec<Glob>,
+}
+
+/// Any directory that isn't hidden
+static DEFAULT_DIRECTORIES: &str = "**/[!.]*";
+/// Workflows and other YAML files under `.github`
+static GITHUB_YAML: &[&str] = &["**/.github/**/*.yml", "**/.github/**/*.yaml"];
+
+impl Interest {
+    /// Files matching any of `patterns`, skipping hidden directories.
+    ///
+    /// Panics if a pattern is invalid, see [`glob`] for fallible parsing.
+    pub fn files(patterns: &[&str]) -> Self {
+        Self {
+            files: patterns
+                .iter()
+                .map(|pattern| glob(pattern).unwrap())
+                .collect(),
+            directories: vec![glob(DEFAULT_DIRECTORIES).unwrap()],
+        }
+    }
+
+    /// Also scans files matching `patterns`.
+    pub fn and_files(mut self, patterns: &[&str]) -> Self {
+        let patterns = patterns.iter().map(|pattern| glob(pattern).unwrap());
+        self.files.extend(patterns);
+        self
+    }
+
+    /// Also walks directories matching `patterns`, eg hidden ones.
+    pub fn and_directories(mut self, patterns: &[&str]) -> Self {
+        let patterns = patterns.iter().map(|pattern| glob(pattern).unwrap());
+        self.directories.extend(patterns);
+        self
+    }
+
+    /// Only walks directories matching `patterns`.
+    pub fn only_directories(mut self, patterns: &[&str]) -> Self {
+        self.directories.clear();
+        self.and_directories(patterns)
+    }
+
+    /// Also scans YAML files under `.github`.
+    fn and_github_yaml(self) -> Self {
+        self.and_files(GITHUB_YAML).and_directories(&["**/.github"])
+    }
+}
+
+/// Parses a glob the way [`Interest`] matches them.
+pub fn glob(pattern: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern).literal_separator(true).build()
+}
+
+/// Lists the releases newer than `current`, newest first, labelling the latest
+/// patch, minor, and overall ones. Releases pair a version with its release
+/// time, and `wrap` stores a version the way the manager writes it.
+fn candidates(
+    current: &str,
+    releases: impl IntoIterator<Item = (String, Option<String>)>,
+    wrap: impl Fn(String) -> Version,
+) -> Vec<Candidate> {
+    let Some((_, current)) = split_version(current) else {
+        return Vec::new();
+    };
+    let pad = |mut numbers: Vec<u64>| {
+        numbers.resize(numbers.len().max(3), 0);
+        numbers
+    };
+    let current = pad(current);
+
+    let mut newer = releases
+        .into_iter()
+        .filter_map(|(version, released)| {
+            let numbers = pad(split_version(&version)?.1);
+            (numbers > current).then_some((numbers, version, released))
+        })
+        .collect::<Vec<_>>();
+    newer.sort_by(|a, b| b.0.cmp(&a.0));
+    newer.dedup_by(|a, b| a.0 == b.0);
+
+    let mut candidates = newer
+        .iter()
+        .map(|(_, version, released)| Candidate {
+            version: wrap(version.clone()),
+            // Only the date of timestamps like `2024-05-01T12:00:00Z`
+            released: released
+                .as_deref()
+                .map(|time| time.split('T').next().unwrap().to_owned()),
+            labels: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    for (label, same) in [("latest patch", 2), ("latest minor", 1), ("latest", 0)] {
+        let latest = newer
+            .iter()
+            .position(|(numbers, ..)| numbers[..same] == current[..same]);
+        if let Some(i) = latest {
+            candidates[i].labels.push(label.to_owned());
+        }
+    }
+
+    candidates
+}
+
+/// Finds the newest of `versions` past `current`, cut to as many numbers as
+/// `current` has, eg `22` for `node 20`.
+fn newest(current: &str, versions: &[String]) -> Option<String> {
+    newer_numbers(current, versions)?
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1))
+        .map(|(newest, _)| newest)
+}
+
+/// Lists the versions past `current` to pick from, cut and written like
+/// [`newest`].
+fn newer(current: &str, versions: &[String]) -> Vec<Candidate> {
+    let releases = newer_numbers(current, versions)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(version, _)| (version, None));
+    candidates(current, releases, Version::SemVer)
+}
+
+/// Cuts the `versions` past `current` to as many numbers as `current` has,
+/// pairing each with the prefix of `current`, eg `3.12` for `python 3.11`.
+fn newer_numbers(current: &str, versions: &[String]) -> Option<Vec<(String, Vec<u64>)>> {
+    let (prefix, current) = split_version(current)?;
+    let newer = versions
+        .iter()
+        .filter_map(|version| {
+            let (_, mut numbers) = split_version(version)?;
+            if numbers.len() < current.len() {
+                return None;
+            }
+            numbers.truncate(current.len());
+            Some(numbers)
+        })
+        .filter(|numbers| *numbers > current)
+        .map(|numbers| {
+            let version = numbers
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(".");
+            (format!("{prefix}{version}"), numbers)
+        })
+        .collect();
+    Some(newer)
+}
+
+/// Splits a release like `v1.2.3` or `Python 3.12.1` into whatever precedes
+/// the numbers and the numbers, rejecting pre-releases.
+fn split_version(version: &str) -> Option<(&str, Vec<u64>)> {
+    let start = version.find(|c: char| c.is_ascii_digit())?;
+    let numbers = version[start..]
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some((&version[..start], numbers))
+}
+
+/// Moves a tag, or a commit with or without its tag, to the newest version tag
+/// of the repository at `url`, adding the newer tags to `candidates`.
+fn find_tag_updates(url: &str, version: &Version, candidates: &mut Vec<Candidate>) -> Updates {
+    let upstream = git::open_repo(&git::git_url(url));
+    let Some(latest) = git::latest_tag(&upstream) else {
+        log::warn!("could not find any version tags of {url}");
+        return Updates::Failed;
+    };
+
+    // Bare commits have no version to compare tags to
+    if let Version::SemVer(current) | Version::GitPinnedTag { tag: current, .. } = version {
+        let tags = git::tags(&upstream).into_iter().map(|tag| (tag, None));
+        *candidates = self::candidates(current, tags, |tag| match version {
+            Version::GitPinnedTag { repo, .. } => Version::GitPinnedTag {
+                repo: repo.clone(),
+                commit: git::tag_commit(&upstream, &tag).unwrap(),
+                tag,
+            },
+            _ => Version::SemVer(tag),
+        });
+    }
+
+    match version {
+        Version::SemVer(tag) => {
+            // Tags like `v1` may point to the same commit as the latest one
+            if latest.name == *tag
+                || git::tag_commit(&upstream, tag).is_some_and(|commit| commit == latest.commit)
+            {
+                Updates::None
+            } else {
+                Updates::Found(Version::SemVer(latest.name))
+            }
+        }
+        Version::GitPinnedTag { repo, commit, .. } if latest.commit != *commit => {
+            Updates::Found(Version::GitPinnedTag {
+                repo: repo.clone(),
+                commit: latest.commit,
+                tag: latest.name,
+            })
+        }
+        Version::GitCommit { repo, commit } if latest.commit != *commit => {
+            Updates::Found(Version::GitCommit {
+                repo: repo.clone(),
+                commit: latest.commit,
+            })
+        }
+        Version::GitPinnedTag { .. } | Version::GitCommit { .. } => Updates::None,
+        Version::OciPinnedTag { .. } => unreachable!(),
+    }
+}
+
+/// Whether `path` is a workflow or other YAML file under `.github`.
+fn is_github_yaml(path: &Utf8Path) -> bool {
+    path.components()
+        .any(|component| component.as_str() == ".github")
+        && matches!(path.extension(), Some("yml" | "yaml"))
 }
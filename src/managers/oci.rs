@@ -0,0 +1,308 @@
+mod reference;
+mod registry;
+
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+
+use camino::Utf8Path;
+
+use self::reference::Reference;
+use crate::DepCollector;
+use crate::config;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+use crate::yaml::{self, Scalar, Segment};
+
+/// Internal and display names of the places images are referenced from
+static FROM_KIND: (&str, &str) = ("from", "Base image");
+static SERVICE_KIND: (&str, &str) = ("service", "Service");
+static CONTAINER_KIND: (&str, &str) = ("container", "Container");
+static DOCKERFILES: &[&str] = &[
+    "**/Dockerfile",
+    "**/Containerfile",
+    "**/Dockerfile.*",
+    "**/*.Dockerfile",
+    "**/*.dockerfile",
+];
+/// Compose files like `compose.yaml` or `docker-compose.override.yml`
+static COMPOSE_FILES: &[&str] = &[
+    "**/compose.{yml,yaml}",
+    "**/compose.*.{yml,yaml}",
+    "**/docker-compose.{yml,yaml}",
+    "**/docker-compose.*.{yml,yaml}",
+];
+
+pub(super) struct Manager {
+    client: registry::Client,
+}
+
+struct Found {
+    kind: (&'static str, &'static str),
+    reference: String,
+    location: Location,
+}
+
+enum Location {
+    /// Byte range in a Dockerfile
+    Text(Range<usize>),
+    Yaml(Scalar),
+}
+
+impl Manager {
+    pub(super) fn new(config: &config::Oci) -> Self {
+        Self {
+            client: registry::Client::new(config),
+        }
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "OCI"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(DOCKERFILES)
+            .and_files(COMPOSE_FILES)
+            .and_github_yaml()
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        // Neither Dockerfiles nor workflows have a fixed name, so track the
+        // file itself
+        let path_id = collector.push_path(path.to_owned());
+        let text = fs::read_to_string(path).unwrap();
+
+        let mut seen = HashSet::new();
+        for found in find_references(path, &text) {
+            if !seen.insert((found.kind.0, found.reference.clone())) {
+                continue;
+            }
+
+            let Some(reference) = Reference::parse(&found.reference) else {
+                log::debug!("{path}: skipping image {}", found.reference);
+                continue;
+            };
+            let Some(tag) = reference.tag else {
+                log::debug!("{path}: skipping untagged image {}", found.reference);
+                continue;
+            };
+
+            let (kind, title) = found.kind;
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind: Some(collector.get_kind_id(kind.to_owned(), || title.to_owned())),
+                name: reference.image.to_owned(),
+                renamed: None,
+                registry: None,
+                protocol: None,
+                version: match reference.digest {
+                    Some(digest) => Version::OciPinnedTag {
+                        image: reference.image.to_owned(),
+                        tag: tag.to_owned(),
+                        digest: digest.to_owned(),
+                    },
+                    None => Version::SemVer(tag.to_owned()),
+                },
+            });
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        _notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let (image, tag, digest) = match &dep.version {
+            Version::OciPinnedTag { image, tag, digest } => (image, tag, Some(digest)),
+            Version::SemVer(tag) => (&dep.name, tag, None),
+            _ => unreachable!(),
+        };
+
+        let Some(tags) = self.client.tags(image) else {
+            return Updates::Failed;
+        };
+        let newest = reference::newest_tag(tag, &tags);
+
+        // Picking a tag of a digest-pinned image would need its digest too
+        if digest.is_none()
+            && let Some((pattern, current)) = reference::split_tag(tag)
+        {
+            let releases = tags.iter().filter_map(|other| {
+                let (other_pattern, numbers) = reference::split_tag(other)?;
+                (other_pattern == pattern).then(|| (numbers.to_owned(), None))
+            });
+            *candidates = super::candidates(current, releases, |numbers| {
+                Version::SemVer(format!("{}{numbers}{}", pattern.prefix, pattern.suffix))
+            });
+        }
+
+        let Some(digest) = digest else {
+            return match newest {
+                Some(newest) => Updates::Found(Version::SemVer(newest.to_owned())),
+                None => Updates::None,
+            };
+        };
+
+        // Even without a newer tag, the current one may have been rebuilt
+        let latest_tag = newest.unwrap_or(tag);
+        let Some(latest_digest) = self.client.digest(image, latest_tag) else {
+            return Updates::Failed;
+        };
+
+        if latest_tag == tag && latest_digest == *digest {
+            Updates::None
+        } else {
+            Updates::Found(Version::OciPinnedTag {
+                image: image.clone(),
+                tag: latest_tag.to_owned(),
+                digest: latest_digest,
+            })
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let current = match &dep.version {
+            Version::OciPinnedTag { tag, digest, .. } => (tag, Some(digest)),
+            Version::SemVer(tag) => (tag, None),
+            _ => unreachable!(),
+        };
+        let latest = match version {
+            Version::OciPinnedTag { image, tag, digest } => format!("{image}:{tag}@{digest}"),
+            Version::SemVer(tag) => format!("{}:{tag}", dep.name),
+            _ => unreachable!(),
+        };
+
+        let path = deps.path(dep.path.unwrap());
+        let mut text = fs::read_to_string(path).unwrap();
+        let kind = deps.internal_kind(dep.kind.unwrap());
+
+        let found = find_references(path, &text)
+            .into_iter()
+            .filter(|found| found.kind.0 == kind)
+            .filter(|found| {
+                Reference::parse(&found.reference).is_some_and(|reference| {
+                    reference.image == dep.name
+                        && reference.tag == Some(current.0)
+                        && reference.digest == current.1.map(String::as_str)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if found.is_empty() {
+            log::warn!("{path}: could not find {} {}", dep.name, dep.version);
+            return;
+        }
+
+        for found in found.iter().rev() {
+            match &found.location {
+                Location::Text(range) => text.replace_range(range.clone(), &latest),
+                Location::Yaml(scalar) => yaml::replace(&mut text, scalar, &latest),
+            }
+        }
+        fs::write(path, text).unwrap();
+    }
+}
+
+fn is_dockerfile(path: &Utf8Path) -> bool {
+    path.file_name().is_some_and(|name| {
+        matches!(name, "Dockerfile" | "Containerfile")
+            || name.starts_with("Dockerfile.")
+            || name.ends_with(".Dockerfile")
+            || name.ends_with(".dockerfile")
+    })
+}
+
+fn find_references(path: &Utf8Path, text: &str) -> Vec<Found> {
+    if is_dockerfile(path) {
+        return dockerfile_references(text);
+    }
+
+    let scalars = match yaml::scalars(text) {
+        Ok(scalars) => scalars,
+        Err(err) => {
+            log::warn!("{path}: failed to parse: {err}");
+            return Vec::new();
+        }
+    };
+
+    let is_github = super::is_github_yaml(path);
+    let kind = |at: &[Segment]| {
+        let keys = at
+            .iter()
+            .map(|segment| match segment {
+                Segment::Key(key) => Some(key.as_str()),
+                Segment::Index(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        match keys.as_slice() {
+            ["jobs", _, "container"] | ["jobs", _, "container", "image"] if is_github => {
+                Some(CONTAINER_KIND)
+            }
+            ["jobs", _, "services", _, "image"] if is_github => Some(SERVICE_KIND),
+            ["services", _, "image"] if !is_github => Some(SERVICE_KIND),
+            _ => None,
+        }
+    };
+
+    scalars
+        .into_iter()
+        .filter_map(|(at, scalar)| {
+            Some(Found {
+                kind: kind(&at)?,
+                reference: scalar.value.clone(),
+                location: Location::Yaml(scalar),
+            })
+        })
+        .collect()
+}
+
+/// Finds the images of `FROM` instructions, skipping `scratch` and earlier
+/// build stages.
+fn dockerfile_references(text: &str) -> Vec<Found> {
+    let mut found = Vec::new();
+    let mut stages = HashSet::new();
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+
+        let mut tokens = line
+            .split_whitespace()
+            .map(|token| (token, token.as_ptr().addr() - line.as_ptr().addr()));
+        if !tokens
+            .next()
+            .is_some_and(|(instruction, _)| instruction.eq_ignore_ascii_case("FROM"))
+        {
+            continue;
+        }
+
+        let mut tokens = tokens.skip_while(|(token, _)| token.starts_with("--"));
+        let Some((image, start)) = tokens.next() else {
+            continue;
+        };
+
+        let is_stage = image == "scratch" || stages.contains(&image.to_lowercase());
+        if let Some((_, _)) = tokens
+            .next()
+            .filter(|(token, _)| token.eq_ignore_ascii_case("AS"))
+            && let Some((stage, _)) = tokens.next()
+        {
+            stages.insert(stage.to_lowercase());
+        }
+
+        if !is_stage {
+            let start = line_offset + start;
+            found.push(Found {
+                kind: FROM_KIND,
+                reference: image.to_owned(),
+                location: Location::Text(start..start + image.len()),
+            });
+        }
+    }
+
+    found
+}
@@ -0,0 +1,94 @@
+//! Parses image references like `ghcr.io/owner/image:1.2-slim@sha256:...` and
+//! compares tags that share a pattern.
+
+static DOCKER_HUB: &str = "docker.io";
+
+pub(super) struct Reference<'a> {
+    /// Image name as written, including any registry
+    pub(super) image: &'a str,
+    pub(super) tag: Option<&'a str>,
+    pub(super) digest: Option<&'a str>,
+}
+
+impl<'a> Reference<'a> {
+    pub(super) fn parse(reference: &'a str) -> Option<Self> {
+        // Skip references built from build args or environment variables
+        if reference.is_empty() || reference.contains(['$', '{', ' ']) {
+            return None;
+        }
+
+        let (rest, digest) = match reference.split_once('@') {
+            Some((rest, digest)) => (rest, Some(digest)),
+            None => (reference, None),
+        };
+
+        let last_slash = rest.rfind('/').map_or(0, |i| i + 1);
+        let (image, tag) = match rest[last_slash..].rsplit_once(':') {
+            Some((_, tag)) => (&rest[..rest.len() - tag.len() - 1], Some(tag)),
+            None => (rest, None),
+        };
+
+        Some(Self { image, tag, digest })
+    }
+}
+
+/// Splits an image name into its registry host and repository, following the
+/// Docker Hub defaults for names without a host.
+pub(super) fn split_image(image: &str) -> (&str, String) {
+    match image.split_once('/') {
+        Some((host, repository)) if host.contains(['.', ':']) || host == "localhost" => {
+            (host, repository.to_owned())
+        }
+        Some(_) => (DOCKER_HUB, image.to_owned()),
+        None => (DOCKER_HUB, format!("library/{image}")),
+    }
+}
+
+/// The shape of a tag like `v1.83-slim`: any prefix, the dotted numbers, and
+/// what follows them.
+#[derive(PartialEq, Eq)]
+pub(super) struct Pattern<'a> {
+    pub(super) prefix: &'a str,
+    numbers: usize,
+    pub(super) suffix: &'a str,
+}
+
+fn parse_tag(tag: &str) -> Option<(Pattern<'_>, Vec<u64>)> {
+    let start = tag.find(|c: char| c.is_ascii_digit())?;
+    let end = tag[start..]
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .map_or(tag.len(), |end| start + end);
+    let version = tag[start..end].trim_end_matches('.');
+    let end = start + version.len();
+
+    let numbers = version
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    let pattern = Pattern {
+        prefix: &tag[..start],
+        numbers: numbers.len(),
+        suffix: &tag[end..],
+    };
+    Some((pattern, numbers))
+}
+
+/// Splits `tag` into its pattern and its numbers as written, eg `1.83` for
+/// `v1.83-slim`.
+pub(super) fn split_tag(tag: &str) -> Option<(Pattern<'_>, &str)> {
+    let (pattern, _) = parse_tag(tag)?;
+    let numbers = &tag[pattern.prefix.len()..tag.len() - pattern.suffix.len()];
+    Some((pattern, numbers))
+}
+
+/// Finds the newest of `tags` with the same pattern as `current`.
+pub(super) fn newest_tag<'a>(current: &str, tags: &'a [String]) -> Option<&'a str> {
+    let (pattern, current) = parse_tag(current)?;
+    tags.iter()
+        .filter_map(|tag| {
+            let (other, numbers) = parse_tag(tag)?;
+            (other == pattern && numbers > current).then_some((numbers, tag.as_str()))
+        })
+        .max()
+        .map(|(_, tag)| tag)
+}
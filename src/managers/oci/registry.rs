@@ -0,0 +1,187 @@
+//! Client for the OCI distribution API, fetching the anonymous bearer tokens
+//! that registries like Docker Hub and GHCR ask for.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use sha2::{Digest as _, Sha256};
+use ureq::Body;
+use ureq::http::{Response, StatusCode};
+
+use super::reference;
+use crate::config;
+
+static DOCKER_HUB_URL: &str = "https://registry-1.docker.io";
+static MANIFEST_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
+
+pub(super) struct Client {
+    agent: ureq::Agent,
+    mirrors: HashMap<String, String>,
+    /// Bearer tokens by repository
+    tokens: Mutex<HashMap<String, String>>,
+}
+
+#[derive(Clone, Copy)]
+enum Method {
+    Get,
+    Head,
+}
+
+impl Client {
+    pub(super) fn new(config: &config::Oci) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .new_agent();
+        Self {
+            agent,
+            mirrors: config.mirrors.clone(),
+            tokens: Mutex::default(),
+        }
+    }
+
+    fn base_url(&self, host: &str) -> String {
+        if let Some(mirror) = self.mirrors.get(host) {
+            return mirror.trim_end_matches('/').to_owned();
+        }
+
+        match host {
+            "docker.io" => DOCKER_HUB_URL.to_owned(),
+            _ if host.starts_with("localhost") || host.starts_with("127.0.0.1") => {
+                format!("http://{host}")
+            }
+            _ => format!("https://{host}"),
+        }
+    }
+
+    /// Lists every tag of `image`, following pagination.
+    pub(super) fn tags(&self, image: &str) -> Option<Vec<String>> {
+        let (host, repository) = reference::split_image(image);
+        let base = self.base_url(host);
+
+        let mut tags = Vec::new();
+        let mut url = format!("{base}/v2/{repository}/tags/list");
+        loop {
+            let mut response = self.request(Method::Get, &repository, &url)?;
+            let next = response
+                .headers()
+                .get("Link")
+                .and_then(|link| link.to_str().ok())
+                .filter(|link| link.contains("rel=\"next\""))
+                .and_then(|link| Some(link.split_once('<')?.1.split_once('>')?.0.to_owned()));
+
+            let body = response.body_mut().read_to_string().ok()?;
+            let body: serde_json::Value = serde_json::from_str(&body).ok()?;
+            let page = body["tags"].as_array().into_iter().flatten();
+            tags.extend(page.filter_map(|tag| Some(tag.as_str()?.to_owned())));
+
+            match next {
+                Some(next) if next.starts_with('/') => url = format!("{base}{next}"),
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+
+        Some(tags)
+    }
+
+    /// Resolves the digest of the manifest `tag` points to, which is a
+    /// manifest list for multi-platform images.
+    pub(super) fn digest(&self, image: &str, tag: &str) -> Option<String> {
+        let (host, repository) = reference::split_image(image);
+        let url = format!("{}/v2/{repository}/manifests/{tag}", self.base_url(host));
+
+        let content_digest = |response: &Response<Body>| {
+            let digest = response.headers().get("Docker-Content-Digest")?;
+            Some(digest.to_str().ok()?.to_owned())
+        };
+
+        let response = self.request(Method::Head, &repository, &url)?;
+        if let Some(digest) = content_digest(&response) {
+            return Some(digest);
+        }
+
+        // Not every registry sends the header, so hash the manifest instead
+        let mut response = self.request(Method::Get, &repository, &url)?;
+        if let Some(digest) = content_digest(&response) {
+            return Some(digest);
+        }
+        let body = response.body_mut().read_to_vec().ok()?;
+        Some(format!("sha256:{}", hex::encode(Sha256::digest(body))))
+    }
+
+    fn request(&self, method: Method, repository: &str, url: &str) -> Option<Response<Body>> {
+        let send = |token: Option<&str>| {
+            let request = match method {
+                Method::Get => self.agent.get(url),
+                Method::Head => self.agent.head(url),
+            };
+            let request = request.header("Accept", MANIFEST_TYPES);
+            let request = match token {
+                Some(token) => request.header("Authorization", format!("Bearer {token}")),
+                None => request,
+            };
+            request.call().inspect_err(|err| {
+                log::warn!("failed to fetch {url}: {err}");
+            })
+        };
+
+        let token = self.tokens.lock().unwrap().get(repository).cloned();
+        let mut response = send(token.as_deref()).ok()?;
+
+        if response.status() == StatusCode::UNAUTHORIZED
+            && let Some(challenge) = response.headers().get("WWW-Authenticate")
+            && let Ok(challenge) = challenge.to_str()
+            && let Some(token) = self.fetch_token(challenge)
+        {
+            response = send(Some(&token)).ok()?;
+            let mut tokens = self.tokens.lock().unwrap();
+            tokens.insert(repository.to_owned(), token);
+        }
+
+        if response.status() == StatusCode::NOT_FOUND {
+            log::debug!("{url} was not found");
+            return None;
+        } else if !response.status().is_success() {
+            log::warn!("failed to fetch {url}: {}", response.status());
+            return None;
+        }
+
+        Some(response)
+    }
+
+    /// Requests an anonymous token as described by a `WWW-Authenticate`
+    /// challenge like `Bearer realm="...",service="...",scope="..."`.
+    fn fetch_token(&self, challenge: &str) -> Option<String> {
+        let mut rest = challenge.strip_prefix("Bearer ")?;
+        let mut params = HashMap::new();
+        while let Some((key, value)) = rest.split_once("=\"") {
+            let (value, after) = value.split_once('"')?;
+            params.insert(key.trim_start_matches([',', ' ']), value);
+            rest = after;
+        }
+
+        let realm = params.remove("realm")?;
+        let mut request = self.agent.get(realm);
+        for (key, value) in params {
+            request = request.query(key, value);
+        }
+
+        let mut response = request
+            .call()
+            .inspect_err(|err| log::warn!("failed to fetch token from {realm}: {err}"))
+            .ok()?;
+        if !response.status().is_success() {
+            log::warn!("failed to fetch token from {realm}: {}", response.status());
+            return None;
+        }
+
+        let body = response.body_mut().read_to_string().ok()?;
+        let body: serde_json::Value = serde_json::from_str(&body).ok()?;
+        let token = body["token"].as_str().or(body["access_token"].as_str())?;
+        Some(token.to_owned())
+    }
+}
@@ -0,0 +1,743 @@
+pub(super) mod npmrc;
+mod spec;
+mod tool;
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::sync::{Mutex, OnceLock};
+
+use base64::Engine as _;
+use camino::{Utf8Path, Utf8PathBuf};
+use facet::Facet;
+
+use self::spec::Spec;
+pub(super) use self::tool::Tool;
+use crate::DepCollector;
+use crate::config::{self, RangePolicy};
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+
+static ALIAS_PROTOCOL: &str = "npm:";
+/// Protocol marking `engines.node`, which is looked up in the Node release
+/// index rather than the npm registry
+static NODE_PROTOCOL: &str = "node:";
+/// Engines other than Node that are published as npm packages
+static ENGINE_PACKAGES: &[&str] = &["npm", "pnpm", "yarn"];
+static WORKSPACE_FILE: &str = "pnpm-workspace.yaml";
+/// Internal kind for `overrides` in `pnpm-workspace.yaml`
+static WORKSPACE_OVERRIDES: &str = "pnpm-workspace:overrides";
+static PACKAGE_MANAGER: &str = "packageManager";
+pub(super) static DEFAULT_NODE_INDEX: &str = "https://nodejs.org/dist/index.json";
+
+pub(super) struct Manager {
+    tool: Tool,
+    npmrc: OnceLock<npmrc::Npmrc>,
+    ranges: RangePolicy,
+    node_index: String,
+    /// Newest Node LTS release, once fetched
+    node_lts: OnceLock<Option<String>>,
+    /// `patchedDependencies` keyed by package name
+    patches: Mutex<HashMap<String, Vec<Patch>>>,
+}
+
+struct Patch {
+    key: String,
+    version: Option<String>,
+    file: String,
+}
+
+impl Manager {
+    pub(super) fn new(tool: Tool, config: &config::Npm) -> Self {
+        Self {
+            tool,
+            npmrc: OnceLock::new(),
+            ranges: config.ranges,
+            node_index: config
+                .node_index
+                .clone()
+                .unwrap_or_else(|| DEFAULT_NODE_INDEX.to_owned()),
+            node_lts: OnceLock::new(),
+            patches: Mutex::default(),
+        }
+    }
+
+    /// Fetches the packument of `name`, which lists every published version
+    /// along with the dist-tags.
+    fn packument(&self, name: &str) -> Option<serde_json::Value> {
+        let npmrc = self.npmrc.get_or_init(npmrc::Npmrc::load);
+        let data = match npmrc.get(name, "") {
+            Ok(data) => data,
+            Err(err) => {
+                log::warn!("failed to fetch {name}: {err}");
+                return None;
+            }
+        };
+        match serde_json::from_slice(&data) {
+            Ok(packument) => Some(packument),
+            Err(err) => {
+                log::warn!("failed to parse packument of {name}: {err}");
+                None
+            }
+        }
+    }
+
+    fn latest_node_lts(&self) -> Option<String> {
+        let fetch = || {
+            let index = &self.node_index;
+            let data = match ureq::get(index).call() {
+                Ok(mut response) => response.body_mut().read_to_vec().ok()?,
+                Err(err) => {
+                    log::warn!("failed to fetch Node releases from {index}: {err}");
+                    return None;
+                }
+            };
+
+            // Releases are listed newest first, with `lts` either `false` or a
+            // codename
+            let releases: serde_json::Value = serde_json::from_slice(&data).ok()?;
+            let latest = releases
+                .as_array()?
+                .iter()
+                .find(|release| release["lts"].is_string())?;
+            let version = latest["version"].as_str()?;
+            Some(version.strip_prefix('v').unwrap_or(version).to_owned())
+        };
+
+        self.node_lts.get_or_init(fetch).clone()
+    }
+
+    /// Computes the hash corepack expects after `+` in `packageManager`, from
+    /// the registry's integrity for `name@version`.
+    fn corepack_hash(&self, name: &str, version: &str) -> Option<String> {
+        let npmrc = self.npmrc.get_or_init(npmrc::Npmrc::load);
+        let data = npmrc.get(name, &format!("/{version}")).ok()?;
+        let VersionData { dist } = facet_json::from_slice(&data).ok()?;
+
+        let (algorithm, digest) = dist.integrity.split_once('-')?;
+        let digest = base64::engine::general_purpose::STANDARD
+            .decode(digest)
+            .ok()?;
+        Some(format!("{algorithm}.{}", hex::encode(digest)))
+    }
+
+    fn add_patches(&self, patches: PackageDeps) {
+        let mut all = self.patches.lock().unwrap();
+        for (key, file) in patches {
+            let (name, version) = split_version(&key);
+            let patch = Patch {
+                version: version.map(str::to_owned),
+                key: key.clone(),
+                file,
+            };
+            all.entry(name.to_owned()).or_default().push(patch);
+        }
+    }
+
+    /// Notes any patches that apply to the current version of `dep`.
+    fn note_patches(&self, dep: &Dep, current: &str, notes: &mut Vec<String>) {
+        let patches = self.patches.lock().unwrap();
+        for patch in patches.get(&dep.name).into_iter().flatten() {
+            if patch.version.as_deref().is_none_or(|v| v == current) {
+                notes.push(format!(
+                    "`{}` is patched by `{}`, which may need updating",
+                    patch.key, patch.file
+                ));
+            }
+        }
+    }
+
+    /// Whether applying an update to `current` would change a range that
+    /// should only be reported.
+    fn report_only(&self, current: &str) -> bool {
+        match Spec::parse(current) {
+            Spec::Range { .. } => self.ranges == RangePolicy::Report,
+            _ => false,
+        }
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        self.tool.name()
+    }
+
+    fn interest(&self) -> super::Interest {
+        let interest = super::Interest::files(&["**/package.json"]);
+        if self.tool == Tool::Pnpm {
+            interest.and_files(&["**/pnpm-workspace.yaml"])
+        } else {
+            interest
+        }
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        // Each package belongs to the tool of its closest lockfile
+        if path.file_name() == Some("package.json")
+            && Tool::detect(path.parent().unwrap()).0 != self.tool
+        {
+            return;
+        }
+
+        let path_id = collector.push_path(path.parent().unwrap().into());
+
+        if path.file_name() == Some(WORKSPACE_FILE) {
+            self.scan_workspace(path, path_id, collector);
+            return;
+        }
+
+        let raw = std::fs::read(path).unwrap();
+        let package = match facet_json::from_slice::<Package>(&raw) {
+            Ok(package) => package,
+            Err(err) => {
+                log::warn!("{path}: failed to parse: {err}");
+                return;
+            }
+        };
+
+        macro_rules! scan_inner {
+            ($key:ident, $title:literal) => {
+                scan_inner(
+                    collector,
+                    path_id,
+                    collector.get_kind_id(stringify!($key).to_owned(), || $title.to_owned()),
+                    package.$key,
+                    plain_name,
+                )
+            };
+            (short $key:ident, $title:literal) => {
+                scan_inner(
+                    collector,
+                    path_id,
+                    collector
+                        .get_kind_id(concat!(stringify!($key), "Dependencies").to_owned(), || {
+                            $title.to_owned()
+                        }),
+                    package.$key,
+                    plain_name,
+                )
+            };
+        }
+
+        scan_inner!(dependencies, "Runtime");
+        scan_inner!(short dev, "Dev");
+        scan_inner!(short peer, "Peer");
+        scan_inner!(short optional, "Optional");
+
+        let overrides = collector.get_kind_id("overrides".to_owned(), || "Overrides".to_owned());
+        scan_inner(
+            collector,
+            path_id,
+            overrides,
+            top_level_overrides(path, &raw),
+            override_target,
+        );
+
+        if matches!(self.tool, Tool::Yarn | Tool::Bun) {
+            let resolutions =
+                collector.get_kind_id("resolutions".to_owned(), || "Resolutions".to_owned());
+            scan_inner(
+                collector,
+                path_id,
+                resolutions,
+                package.resolutions,
+                resolution_target,
+            );
+        }
+
+        if self.tool == Tool::Pnpm {
+            let pnpm_overrides =
+                collector.get_kind_id("pnpm.overrides".to_owned(), || "Overrides".to_owned());
+            scan_inner(
+                collector,
+                path_id,
+                pnpm_overrides,
+                package.pnpm.overrides,
+                override_target,
+            );
+            self.add_patches(package.pnpm.patched);
+        }
+
+        if let Some(package_manager) = package.package_manager {
+            let kind_id =
+                collector.get_kind_id(PACKAGE_MANAGER.to_owned(), || "Package manager".to_owned());
+            let without_hash = package_manager.split('+').next().unwrap();
+            match split_version(without_hash) {
+                (name, Some(version)) if matches!(Spec::parse(version), Spec::Exact { .. }) => {
+                    collector.push_dep(DepInit {
+                        path: Some(path_id),
+                        kind: Some(kind_id),
+                        name: name.to_owned(),
+                        renamed: None,
+                        registry: None,
+                        protocol: None,
+                        version: Version::SemVer(version.to_owned()),
+                    });
+                }
+                _ => log::warn!("{path}: invalid packageManager {package_manager}"),
+            }
+        }
+
+        let mut engines = package.engines;
+        let engines_kind = collector.get_kind_id("engines".to_owned(), || "Engines".to_owned());
+        if let Some(node) = engines.remove("node") {
+            if matches!(Spec::parse(&node), Spec::Exact { .. } | Spec::Range { .. }) {
+                collector.push_dep(DepInit {
+                    path: Some(path_id),
+                    kind: Some(engines_kind),
+                    name: "node".to_owned(),
+                    renamed: None,
+                    registry: None,
+                    protocol: Some(NODE_PROTOCOL.to_owned()),
+                    version: Version::SemVer(node),
+                });
+            } else {
+                log::info!("skipping node: unsupported range {node}");
+            }
+        }
+        engines.retain(|name, range| {
+            let known = ENGINE_PACKAGES.contains(&name.as_str());
+            if !known {
+                log::debug!("{path}: skipping engine {name}: {range}");
+            }
+            known
+        });
+        scan_inner(collector, path_id, engines_kind, engines, plain_name);
+    }
+
+    fn find_updates(
+        &self,
+        dep: &crate::Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        match &dep.version {
+            Version::SemVer(current) => {
+                let (operator, floor) = match Spec::parse(current) {
+                    Spec::Exact { operator, version } | Spec::Range { operator, version } => {
+                        (operator, version)
+                    }
+                    _ => unreachable!(),
+                };
+
+                // The minimum Node version is for the package to decide, so
+                // newer LTS releases are only reported
+                if dep.protocol.as_deref() == Some(NODE_PROTOCOL) {
+                    let Some(lts) = self.latest_node_lts() else {
+                        return Updates::Failed;
+                    };
+                    if !is_floor_of(floor, &lts) {
+                        notes.push(format!("Node {lts} is the newest LTS release"));
+                    }
+                    return Updates::None;
+                }
+
+                let Some(packument) = self.packument(&dep.name) else {
+                    return Updates::Failed;
+                };
+                let Some(version) = packument["dist-tags"]["latest"].as_str().map(str::to_owned)
+                else {
+                    return Updates::Failed;
+                };
+
+                if is_floor_of(floor, &version) {
+                    return Updates::None;
+                }
+
+                self.note_patches(dep, floor, notes);
+                let report_only = self.report_only(current);
+                // Picked versions would be discarded like the update itself
+                if !report_only {
+                    *candidates = super::candidates(floor, releases(&packument), |version| {
+                        Version::SemVer(format!("{operator}{version}"))
+                    });
+                }
+
+                if report_only {
+                    notes.push(format!(
+                        "`{current}` is a range, so {version} is only reported"
+                    ));
+                    Updates::Found(Version::SemVer(version))
+                } else {
+                    Updates::Found(Version::SemVer(format!("{operator}{version}")))
+                }
+            }
+            Version::GitCommit { repo, commit } => {
+                let git = crate::git::open_repo(&spec::clone_url(repo));
+                let Some(latest) = crate::git::default_branch(&git) else {
+                    log::warn!("could not find the default branch of {repo}");
+                    return Updates::Failed;
+                };
+
+                let latest = hex::encode(latest.id.as_slice());
+                if latest.starts_with(commit.as_str()) {
+                    Updates::None
+                } else {
+                    Updates::Found(Version::GitCommit {
+                        repo: repo.clone(),
+                        commit: latest,
+                    })
+                }
+            }
+            Version::GitPinnedTag { repo, tag, .. } => {
+                let git = crate::git::open_repo(&spec::clone_url(repo));
+                let Some(commit) = crate::git::tag_commit(&git, tag) else {
+                    log::warn!("could not find tag {tag} in {repo}");
+                    return Updates::Failed;
+                };
+
+                match crate::git::latest_tag(&git) {
+                    Some(latest) if latest.commit != commit => {
+                        Updates::Found(Version::GitPinnedTag {
+                            repo: repo.clone(),
+                            commit: latest.commit,
+                            tag: latest.name,
+                        })
+                    }
+                    _ => Updates::None,
+                }
+            }
+            Version::OciPinnedTag { .. } => unreachable!(),
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let value = match version {
+            Version::SemVer(_)
+                if let Version::SemVer(current) = &dep.version
+                    && self.report_only(current) =>
+            {
+                log::info!("not changing range for {}", dep.name);
+                return;
+            }
+            Version::SemVer(latest) => {
+                // Keep the current operator, even if the update was found
+                // while ranges were only being reported
+                let Version::SemVer(current) = &dep.version else {
+                    unreachable!()
+                };
+                let latest = latest.trim_start_matches(|c: char| !c.is_ascii_digit());
+                match Spec::parse(current) {
+                    Spec::Exact { operator, .. } | Spec::Range { operator, .. } => {
+                        format!("{operator}{latest}")
+                    }
+                    _ => latest.to_owned(),
+                }
+            }
+            Version::GitCommit { repo, commit } => format!("{repo}#{commit}"),
+            Version::GitPinnedTag { repo, tag, .. } => format!("{repo}#{tag}"),
+            Version::OciPinnedTag { .. } => unreachable!(),
+        };
+
+        let value = if dep.protocol.as_deref() == Some(ALIAS_PROTOCOL) {
+            format!("{ALIAS_PROTOCOL}{}@{value}", dep.name)
+        } else {
+            value
+        };
+
+        let dir = deps.path(dep.path.unwrap());
+        let kind = deps.internal_kind(dep.kind.unwrap());
+        let name = dep.renamed.as_deref().unwrap_or(&dep.name);
+
+        if kind == WORKSPACE_OVERRIDES {
+            let path = dir.join(WORKSPACE_FILE);
+            let mut yaml = fs::read_to_string(&path).unwrap();
+            let overrides = crate::yaml::mapping(&yaml, &["overrides"]).unwrap();
+            let (_, scalar) = overrides.iter().find(|(key, _)| key == name).unwrap();
+            crate::yaml::replace(&mut yaml, scalar, &value);
+            fs::write(path, yaml).unwrap();
+            return;
+        }
+
+        let path = dir.join("package.json");
+        let json = std::fs::read(&path).unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        if kind == PACKAGE_MANAGER {
+            let current = json[PACKAGE_MANAGER].as_str().unwrap();
+            let mut value = format!("{}@{value}", dep.name);
+            if current.contains('+') {
+                match self.corepack_hash(&dep.name, &value[dep.name.len() + 1..]) {
+                    Some(hash) => {
+                        value.push('+');
+                        value.push_str(&hash);
+                    }
+                    None => log::warn!("could not find the integrity of {value}"),
+                }
+            }
+
+            json[PACKAGE_MANAGER] = serde_json::Value::String(value);
+            let writer = File::create(path).unwrap();
+            serde_json::to_writer_pretty(writer, &json).unwrap();
+            return;
+        }
+
+        // Nested kinds like `pnpm.overrides`
+        let pointer = format!("/{}", kind.replace('.', "/"));
+        json.pointer_mut(&pointer)
+            .unwrap()
+            .as_object_mut()
+            .unwrap()
+            .insert(name.to_owned(), serde_json::Value::String(value));
+
+        let writer = File::create(path).unwrap();
+        serde_json::to_writer_pretty(writer, &json).unwrap();
+    }
+
+    fn after_apply(&self, deps: &Deps, applied: &[&Dep]) {
+        let roots = applied
+            .iter()
+            .filter_map(|dep| Tool::detect(deps.path(dep.path.unwrap())).1)
+            .collect::<BTreeSet<_>>();
+
+        for root in roots {
+            log::info!("refreshing {} lockfile in {root}", self.tool.name());
+            let result = self
+                .tool
+                .refresh_lockfile()
+                .dir(&root)
+                .stdin_null()
+                .stdout_null()
+                .run();
+            if let Err(err) = result {
+                log::warn!("failed to refresh lockfile in {root}: {err}");
+            }
+        }
+    }
+
+    fn manifest(&self, deps: &Deps, path: &Utf8Path, kind: Option<usize>) -> Utf8PathBuf {
+        if kind.is_some_and(|kind| deps.internal_kind(kind) == WORKSPACE_OVERRIDES) {
+            path.join(WORKSPACE_FILE)
+        } else {
+            path.join("package.json")
+        }
+    }
+}
+
+impl Manager {
+    fn scan_workspace(&self, path: &Utf8Path, path_id: usize, collector: DepCollector<'_>) {
+        let yaml = fs::read_to_string(path).unwrap();
+        let read = |key| match crate::yaml::mapping(&yaml, &[key]) {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|(key, scalar)| (key, scalar.value))
+                .collect(),
+            Err(err) => {
+                log::warn!("failed to parse {path}: {err}");
+                PackageDeps::new()
+            }
+        };
+
+        let kind_id =
+            collector.get_kind_id(WORKSPACE_OVERRIDES.to_owned(), || "Overrides".to_owned());
+        scan_inner(
+            collector,
+            path_id,
+            kind_id,
+            read("overrides"),
+            override_target,
+        );
+        self.add_patches(read("patchedDependencies"));
+    }
+}
+
+/// Collects dependencies from one section of a manifest. `target` finds the
+/// package name in keys like the selector `foo@1>bar@<2`, and any key that
+/// isn't just the name is kept as the renamed name.
+fn scan_inner(
+    collector: DepCollector<'_>,
+    path_id: usize,
+    kind_id: usize,
+    deps: PackageDeps,
+    target: fn(&str) -> &str,
+) {
+    for (key, mut specifier) in deps {
+        let mut name = target(&key).to_owned();
+
+        let mut protocol = None;
+        if let Some(alias) = specifier.strip_prefix(ALIAS_PROTOCOL) {
+            let Some((target, target_specifier)) = split_alias(alias) else {
+                log::debug!("skipping unversioned alias {key}: {specifier}");
+                continue;
+            };
+
+            protocol = Some(ALIAS_PROTOCOL.to_owned());
+            name = target.to_owned();
+            specifier = target_specifier.to_owned();
+        }
+
+        let renamed = (name != key).then_some(key);
+
+        let version = match Spec::parse(&specifier) {
+            Spec::Local | Spec::Floating => {
+                log::debug!("skipping {name}: {specifier}");
+                continue;
+            }
+            Spec::ComplexRange => {
+                log::info!("skipping {name}: unsupported range {specifier}");
+                continue;
+            }
+            Spec::Exact { .. } | Spec::Range { .. } => Version::SemVer(specifier.clone()),
+            Spec::Git { repo, reference } if spec::is_abbreviated_commit(reference) => {
+                Version::GitCommit {
+                    repo: repo.to_owned(),
+                    commit: reference.to_owned(),
+                }
+            }
+            // The tag's commit is looked up with its updates, so scanning
+            // doesn't need to fetch the repository
+            Spec::Git { repo, reference } => Version::GitPinnedTag {
+                repo: repo.to_owned(),
+                commit: String::new(),
+                tag: reference.to_owned(),
+            },
+        };
+
+        collector.push_dep(DepInit {
+            path: Some(path_id),
+            kind: Some(kind_id),
+            name,
+            renamed,
+            registry: None,
+            protocol,
+            version,
+        });
+    }
+}
+
+/// Lists every version in `packument` that isn't deprecated, with its publish
+/// time.
+fn releases(packument: &serde_json::Value) -> Vec<(String, Option<String>)> {
+    let Some(versions) = packument["versions"].as_object() else {
+        return Vec::new();
+    };
+    versions
+        .iter()
+        .filter(|(_, data)| data.get("deprecated").is_none())
+        .map(|(version, _)| {
+            let time = packument["time"][version].as_str().map(str::to_owned);
+            (version.clone(), time)
+        })
+        .collect()
+}
+
+/// Splits the `<package>@<version>` target of an alias, where `package` may be
+/// scoped.
+fn split_alias(alias: &str) -> Option<(&str, &str)> {
+    match split_version(alias) {
+        (name, Some(version)) => Some((name, version)),
+        (_, None) => None,
+    }
+}
+
+/// Splits `<package>[@<version>]`, where `package` may be scoped.
+fn split_version(s: &str) -> (&str, Option<&str>) {
+    match s.get(1..).and_then(|rest| rest.find('@')) {
+        Some(at) => (&s[..=at], Some(&s[at + 2..])),
+        None => (s, None),
+    }
+}
+
+/// Whether `latest` is no newer than `floor`, which may be partial like `18`.
+fn is_floor_of(floor: &str, latest: &str) -> bool {
+    let parts = floor.split('.').count();
+    let truncated = latest.split('.').take(parts).collect::<Vec<_>>().join(".");
+    floor == truncated || floor == latest
+}
+
+fn plain_name(key: &str) -> &str {
+    key
+}
+
+/// Returns the package a Yarn resolution like `**/parent/@scope/child@^1`
+/// applies to.
+fn resolution_target(key: &str) -> &str {
+    let mut start = 0;
+    let mut segments = key.split('/').peekable();
+    let mut offset = 0;
+    while let Some(segment) = segments.next() {
+        let mut len = segment.len();
+        if segment.starts_with('@')
+            && let Some(name) = segments.next()
+        {
+            len += 1 + name.len();
+        }
+
+        start = offset;
+        offset += len + 1;
+    }
+
+    split_version(&key[start..]).0
+}
+
+/// Reads the npm and Bun `overrides` that pin a version directly, skipping
+/// nested objects like `{"foo": {"bar": "1.0.0"}}`, which only apply below
+/// another package.
+fn top_level_overrides(path: &Utf8Path, raw: &[u8]) -> PackageDeps {
+    let json: serde_json::Value = serde_json::from_slice(raw).unwrap();
+    let Some(overrides) = json.get("overrides").and_then(|o| o.as_object()) else {
+        return PackageDeps::new();
+    };
+
+    overrides
+        .iter()
+        .filter_map(|(key, value)| match value.as_str() {
+            Some(specifier) => Some((key.clone(), specifier.to_owned())),
+            None => {
+                log::debug!("{path}: skipping nested override {key}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the package an override selector like `foo@1>bar@<2` applies to.
+fn override_target(key: &str) -> &str {
+    // `>` also appears in ranges, but only right after `@`, a space, or `<`
+    let parent_end = key
+        .char_indices()
+        .rev()
+        .find(|&(i, c)| {
+            c == '>' && !key[..i].ends_with(['@', ' ', '<', '>']) && !key[i + 1..].starts_with('=')
+        })
+        .map(|(i, _)| i + 1);
+    split_version(&key[parent_end.unwrap_or(0)..]).0
+}
+
+#[derive(Debug, Facet)]
+struct Package {
+    #[facet(default)]
+    dependencies: PackageDeps,
+    #[facet(default, rename = "devDependencies")]
+    dev: PackageDeps,
+    #[facet(default, rename = "peerDependencies")]
+    peer: PackageDeps,
+    #[facet(default, rename = "optionalDependencies")]
+    optional: PackageDeps,
+    #[facet(default)]
+    resolutions: PackageDeps,
+    #[facet(default)]
+    pnpm: PnpmSettings,
+    #[facet(default, rename = "packageManager")]
+    package_manager: Option<String>,
+    #[facet(default)]
+    engines: PackageDeps,
+}
+
+#[derive(Debug, Default, Facet)]
+struct PnpmSettings {
+    #[facet(default)]
+    overrides: PackageDeps,
+    #[facet(default, rename = "patchedDependencies")]
+    patched: PackageDeps,
+}
+
+type PackageDeps = HashMap<String, String>;
+
+#[derive(Debug, Facet)]
+struct VersionData {
+    dist: Dist,
+}
+
+#[derive(Debug, Facet)]
+struct Dist {
+    integrity: String,
+}
@@ -0,0 +1,182 @@
+//! Registry and authentication settings from `.npmrc` files.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+static DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+/// Largest response to read, as packuments of packages with many releases run
+/// well past ureq's default of 10 MB
+static BODY_LIMIT: u64 = 512 * 1024 * 1024;
+
+#[derive(Default)]
+pub(in crate::managers) struct Npmrc {
+    settings: HashMap<String, String>,
+}
+
+impl Npmrc {
+    /// Loads the global, user, and project `.npmrc` files, with later ones
+    /// taking precedence.
+    pub(in crate::managers) fn load() -> Self {
+        let mut npmrc = Self::default();
+
+        let global = env_setting("globalconfig")
+            .map(Utf8PathBuf::from)
+            .or_else(|| {
+                let prefix = env_setting("prefix").unwrap_or_else(|| "/usr/local".to_owned());
+                Some(Utf8Path::new(&prefix).join("etc").join("npmrc"))
+            });
+        let user = env_setting("userconfig")
+            .map(Utf8PathBuf::from)
+            .or_else(|| {
+                let home = dirs::home_dir()?.join(".npmrc");
+                Utf8PathBuf::from_path_buf(home).ok()
+            });
+        let project = Some(Utf8PathBuf::from(".npmrc"));
+
+        for path in [global, user, project].into_iter().flatten() {
+            npmrc.load_file(&path);
+        }
+
+        if let Some(registry) = env_setting("registry") {
+            npmrc.settings.insert("registry".to_owned(), registry);
+        }
+
+        npmrc
+    }
+
+    fn load_file(&mut self, path: &Utf8Path) {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return;
+        };
+        log::debug!("reading npm config from {path}");
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                log::warn!("{path}: ignoring invalid line: {line}");
+                continue;
+            };
+
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            self.settings
+                .insert(expand_env(key.trim()), expand_env(value));
+        }
+    }
+
+    /// Returns the registry URL for the package `name`, always ending in `/`.
+    pub(super) fn registry(&self, name: &str) -> String {
+        let scoped = name
+            .split_once('/')
+            .filter(|(scope, _)| scope.starts_with('@'))
+            .and_then(|(scope, _)| self.settings.get(&format!("{scope}:registry")));
+
+        let registry = scoped
+            .or_else(|| self.settings.get("registry"))
+            .map_or(DEFAULT_REGISTRY, String::as_str);
+
+        if registry.ends_with('/') {
+            registry.to_owned()
+        } else {
+            format!("{registry}/")
+        }
+    }
+
+    /// Returns the `Authorization` header to send to `registry`, if any.
+    pub(super) fn authorization(&self, registry: &str) -> Option<String> {
+        // Like npm's "nerf darts", compare `host/path/` with the trailing
+        // slash on both sides, so only whole hosts and path segments match
+        let with_slash = |s: &str| {
+            if s.ends_with('/') {
+                s.to_owned()
+            } else {
+                format!("{s}/")
+            }
+        };
+        let nerfed = with_slash(registry.split_once("//").map_or(registry, |(_, rest)| rest));
+
+        // Find the most specific `//host/path/:_authToken` that applies
+        let mut best: Option<(usize, String)> = None;
+        for (key, value) in &self.settings {
+            let Some((prefix, setting)) =
+                key.strip_prefix("//").and_then(|key| key.rsplit_once(':'))
+            else {
+                continue;
+            };
+
+            let header = match setting {
+                "_authToken" => format!("Bearer {value}"),
+                "_auth" => format!("Basic {value}"),
+                _ => continue,
+            };
+
+            let prefix = with_slash(prefix);
+            if nerfed.starts_with(&prefix)
+                && best.as_ref().is_none_or(|(len, _)| prefix.len() > *len)
+            {
+                best = Some((prefix.len(), header));
+            }
+        }
+
+        best.map(|(_, header)| header)
+    }
+
+    /// Fetches `path` relative to the registry for the package `name`.
+    pub(in crate::managers) fn get(&self, name: &str, path: &str) -> Result<Vec<u8>, ureq::Error> {
+        let registry = self.registry(name);
+        let mut request = ureq::get(format!("{registry}{}{path}", encode_name(name)));
+        if let Some(authorization) = self.authorization(&registry) {
+            request = request.header("Authorization", authorization);
+        }
+
+        request
+            .call()?
+            .into_body()
+            .with_config()
+            .limit(BODY_LIMIT)
+            .read_to_vec()
+    }
+}
+
+/// Encodes a package name for use in a registry URL, which keeps the `@` of
+/// scoped names but not the `/`.
+fn encode_name(name: &str) -> String {
+    name.replace('/', "%2f")
+}
+
+fn env_setting(key: &str) -> Option<String> {
+    env::var(format!("npm_config_{key}"))
+        .or_else(|_| env::var(format!("NPM_CONFIG_{}", key.to_uppercase())))
+        .ok()
+}
+
+/// Replaces `${VAR}` with the value of the environment variable `VAR`.
+fn expand_env(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+
+        out.push_str(&rest[..start]);
+        let var = &rest[start + 2..start + end];
+        match env::var(var) {
+            Ok(value) => out.push_str(&value),
+            Err(_) => log::warn!("npm config references unset variable {var}"),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
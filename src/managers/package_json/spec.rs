@@ -0,0 +1,141 @@
+//! Classifies dependency specifiers from `package.json`.
+
+pub(super) enum Spec<'a> {
+    /// Workspace, local paths, tarballs, references to other dependencies, and
+    /// other things that can't be updated
+    Local,
+    /// Dist-tags, unpinned git urls, and other things that float on their own
+    Floating,
+    /// An exact version, eg `1.2.3` or `=1.2.3`
+    Exact { operator: &'a str, version: &'a str },
+    /// A range with a single lower bound, eg `^1.2.3`, `~1.2`, or `>=1.2.3`
+    Range { operator: &'a str, version: &'a str },
+    /// Any other range, eg `1.x` or `>=1.2 <2`
+    ComplexRange,
+    /// A git repository pinned to a commit or tag, eg `github:user/repo#v1.2.3`
+    Git { repo: &'a str, reference: &'a str },
+}
+
+static LOCAL_PROTOCOLS: &[&str] = &[
+    "workspace:",
+    "link:",
+    "file:",
+    "portal:",
+    "patch:",
+    "catalog:",
+    "exec:",
+];
+
+static GIT_PREFIXES: &[&str] = &[
+    "github:",
+    "gitlab:",
+    "bitbucket:",
+    "gist:",
+    "git+",
+    "git://",
+];
+
+impl<'a> Spec<'a> {
+    pub(super) fn parse(spec: &'a str) -> Self {
+        let spec = spec.trim();
+
+        // `-` removes a dependency and `$foo` refers to a direct dependency in
+        // overrides
+        if spec == "-"
+            || spec.starts_with('$')
+            || LOCAL_PROTOCOLS.iter().any(|p| spec.starts_with(p))
+        {
+            return Self::Local;
+        }
+
+        let is_url = spec.starts_with("http://") || spec.starts_with("https://");
+        let is_shorthand =
+            !spec.contains(':') && spec.contains('/') && !spec.starts_with(['@', '.', '/', '~']);
+        if GIT_PREFIXES.iter().any(|p| spec.starts_with(p))
+            || is_shorthand
+            || (is_url
+                && spec
+                    .split('#')
+                    .next()
+                    .is_some_and(|url| url.ends_with(".git")))
+        {
+            return match spec.split_once('#') {
+                Some((_, reference))
+                    if reference.is_empty() || reference.starts_with("semver:") =>
+                {
+                    Self::Floating
+                }
+                Some((repo, reference)) => Self::Git { repo, reference },
+                None => Self::Floating,
+            };
+        }
+
+        if is_url || spec.contains(':') {
+            return Self::Local;
+        }
+
+        if spec.starts_with(|c: char| c.is_ascii_alphabetic()) && !spec.starts_with(['v', 'x', 'X'])
+        {
+            return Self::Floating;
+        }
+
+        let (operator, version) = split_operator(spec);
+        if !is_simple_version(version) {
+            Self::ComplexRange
+        } else if matches!(operator, "" | "=" | "v") && core(version).split('.').count() == 3 {
+            Self::Exact { operator, version }
+        } else if matches!(operator, "^" | "~" | ">=") {
+            Self::Range { operator, version }
+        } else {
+            Self::ComplexRange
+        }
+    }
+}
+
+/// Whether a git reference looks like a commit hash, possibly abbreviated to
+/// 7 or more characters, rather than a tag.
+pub(super) fn is_abbreviated_commit(reference: &str) -> bool {
+    reference.len() >= 7 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Converts the repository part of a git specifier into a URL to clone.
+pub(super) fn clone_url(repo: &str) -> String {
+    let hosts = [
+        ("github:", "https://github.com/"),
+        ("gitlab:", "https://gitlab.com/"),
+        ("bitbucket:", "https://bitbucket.org/"),
+        ("gist:", "https://gist.github.com/"),
+    ];
+    for (prefix, host) in hosts {
+        if let Some(path) = repo.strip_prefix(prefix) {
+            let path = path.strip_suffix(".git").unwrap_or(path);
+            return format!("{host}{path}.git");
+        }
+    }
+
+    let url = repo.strip_prefix("git+").unwrap_or(repo);
+    crate::git::git_url(url).into_owned()
+}
+
+fn split_operator(spec: &str) -> (&str, &str) {
+    let end = spec
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(spec.len());
+    let (operator, version) = spec.split_at(end);
+    (operator.trim_end(), version)
+}
+
+/// The part of a version before any pre-release or build metadata.
+fn core(version: &str) -> &str {
+    version.split(['-', '+']).next().unwrap_or_default()
+}
+
+/// Whether `version` is a single, possibly partial, version like `1`, `1.2`,
+/// or `1.2.3-beta.1`
+fn is_simple_version(version: &str) -> bool {
+    !version.is_empty()
+        && !version.contains(char::is_whitespace)
+        && core(version)
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
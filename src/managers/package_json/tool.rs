@@ -0,0 +1,64 @@
+//! The package managers that share the `package.json` format, and how to tell
+//! which one a project uses.
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Tool {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl Tool {
+    const ALL: [Self; 4] = [Self::Npm, Self::Pnpm, Self::Yarn, Self::Bun];
+
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Yarn => "yarn",
+            Self::Bun => "bun",
+        }
+    }
+
+    fn lockfiles(self) -> &'static [&'static str] {
+        match self {
+            Self::Npm => &["package-lock.json", "npm-shrinkwrap.json"],
+            Self::Pnpm => &["pnpm-lock.yaml"],
+            Self::Yarn => &["yarn.lock"],
+            Self::Bun => &["bun.lock", "bun.lockb"],
+        }
+    }
+
+    /// Command that updates the lockfile to match `package.json` without
+    /// installing anything.
+    pub(super) fn refresh_lockfile(self) -> duct::Expression {
+        match self {
+            Self::Npm => duct::cmd!("npm", "install", "--package-lock-only"),
+            Self::Pnpm => duct::cmd!("pnpm", "install", "--lockfile-only"),
+            Self::Yarn => duct::cmd!("yarn", "install", "--mode=update-lockfile"),
+            Self::Bun => duct::cmd!("bun", "install", "--lockfile-only"),
+        }
+    }
+
+    /// Finds the tool managing the package in `dir` from the closest lockfile,
+    /// along with the directory containing it. Projects without a lockfile
+    /// are assumed to use pnpm.
+    pub(super) fn detect(dir: &Utf8Path) -> (Self, Option<Utf8PathBuf>) {
+        for ancestor in dir.ancestors() {
+            for tool in Self::ALL {
+                let found = tool
+                    .lockfiles()
+                    .iter()
+                    .any(|lockfile| ancestor.join(lockfile).is_file());
+                if found {
+                    return (tool, Some(ancestor.to_owned()));
+                }
+            }
+        }
+
+        (Self::Pnpm, None)
+    }
+}
@@ -0,0 +1,369 @@
+//! Managers implemented by external executables, talking JSON lines over
+//! stdio. See `docs/plugins.md` for the protocol.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+
+use anyhow::Context as _;
+use camino::{Utf8Path, Utf8PathBuf};
+use facet::Facet;
+use serde_json::json;
+
+use crate::DepCollector;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+
+static PROTOCOL_VERSION: u64 = 1;
+static EXECUTABLE_PREFIX: &str = concat!(env!("CARGO_PKG_NAME"), "-manager-");
+static KIND_PREFIX: &str = "plugin\0";
+
+pub(super) struct Manager {
+    name: &'static str,
+    interest: super::Interest,
+    process: Mutex<Process>,
+    /// Paths and plugin kinds by id, as `find_updates` has no [`Deps`]
+    paths: Mutex<HashMap<usize, Utf8PathBuf>>,
+    kinds: Mutex<HashMap<usize, String>>,
+}
+
+struct Process {
+    child: Child,
+    /// Taken when dropping, as closing it tells the plugin to exit
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+#[derive(Facet)]
+struct Hello {
+    protocol: u64,
+    name: String,
+    files: Vec<String>,
+    #[facet(default)]
+    directories: Option<Vec<String>>,
+}
+
+#[derive(Facet)]
+struct Scanned {
+    deps: Vec<ScannedDep>,
+}
+
+#[derive(Facet)]
+struct ScannedDep {
+    name: String,
+    version: Version,
+    #[facet(default)]
+    kind: Option<ScannedKind>,
+    #[facet(default)]
+    renamed: Option<String>,
+    #[facet(default)]
+    registry: Option<String>,
+    #[facet(default)]
+    protocol: Option<String>,
+}
+
+#[derive(Facet)]
+struct ScannedKind {
+    id: String,
+    title: String,
+}
+
+#[derive(Facet)]
+struct Found {
+    updates: Updates,
+    #[facet(default)]
+    notes: Vec<String>,
+    #[facet(default)]
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Facet)]
+struct Applied {
+    edits: Vec<Edit>,
+}
+
+#[derive(Facet)]
+struct Done {}
+
+#[derive(Facet)]
+struct Edit {
+    path: Utf8PathBuf,
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// Starts the plugins listed in the config, then the `pupgrade-manager-*`
+/// executables on `PATH`, skipping any that fail to start.
+pub(super) fn all(configured: &[String]) -> Vec<Manager> {
+    let mut programs = configured.iter().map(Utf8PathBuf::from).collect::<Vec<_>>();
+
+    // Like a shell, the first directory on `PATH` with a name wins
+    let mut found = BTreeMap::new();
+    for dir in env::split_paths(&env::var_os("PATH").unwrap_or_default()) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if name.starts_with(EXECUTABLE_PREFIX)
+                && entry
+                    .file_type()
+                    .is_ok_and(|t| t.is_file() || t.is_symlink())
+                && let Ok(path) = Utf8PathBuf::try_from(entry.path())
+            {
+                found.entry(name).or_insert(path);
+            }
+        }
+    }
+    programs.extend(found.into_values());
+
+    programs
+        .iter()
+        .filter_map(|program| match Manager::spawn(program) {
+            Ok(plugin) => Some(plugin),
+            Err(err) => {
+                log::warn!("failed to start plugin {program}: {err:#}");
+                None
+            }
+        })
+        .collect()
+}
+
+impl Manager {
+    fn spawn(program: &Utf8Path) -> anyhow::Result<Self> {
+        let mut child = Command::new(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let process = Process {
+            stdin: child.stdin.take(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+        };
+        let process = Mutex::new(process);
+
+        let hello: Hello = request(
+            &process,
+            json!({ "method": "hello", "protocol": PROTOCOL_VERSION }),
+        )?;
+        if hello.protocol != PROTOCOL_VERSION {
+            anyhow::bail!(
+                "speaks protocol version {}, not {PROTOCOL_VERSION}",
+                hello.protocol
+            );
+        }
+
+        let mut interest = super::Interest::files(&[]);
+        for pattern in &hello.files {
+            interest.files.push(super::glob(pattern)?);
+        }
+        // Without directories, skip hidden ones like other managers
+        if let Some(directories) = &hello.directories {
+            interest.directories = directories
+                .iter()
+                .map(|pattern| super::glob(pattern))
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(Self {
+            name: String::leak(hello.name),
+            interest,
+            process,
+            paths: Mutex::default(),
+            kinds: Mutex::default(),
+        })
+    }
+
+    fn request<T: for<'a> Facet<'a>>(&self, message: serde_json::Value) -> Option<T> {
+        match request(&self.process, message) {
+            Ok(response) => Some(response),
+            Err(err) => {
+                log::warn!("{}: {err:#}", self.name);
+                None
+            }
+        }
+    }
+
+    /// Describes a dependency the way the plugin reported it.
+    fn dep_json(
+        &self,
+        path: Option<&Utf8Path>,
+        kind: Option<&str>,
+        dep: &Dep,
+    ) -> serde_json::Value {
+        json!({
+            "path": path.map(Utf8Path::as_str),
+            "kind": kind,
+            "name": dep.name,
+            "renamed": dep.renamed,
+            "registry": dep.registry,
+            "protocol": dep.protocol,
+            "version": to_json(&dep.version),
+        })
+    }
+
+    /// Like [`dep_json`](Self::dep_json), resolving ids through `deps`.
+    fn resolved_dep_json(&self, deps: &Deps, dep: &Dep) -> serde_json::Value {
+        let path = dep.path.map(|id| deps.path(id));
+        let kind = dep
+            .kind
+            .map(|id| deps.internal_kind(id))
+            .and_then(|kind| kind.strip_prefix(KIND_PREFIX))
+            .and_then(|kind| kind.split_once('\0'))
+            .map(|(_, kind)| kind);
+        self.dep_json(path, kind, dep)
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn interest(&self) -> super::Interest {
+        self.interest.clone()
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        let Some(Scanned { deps }) =
+            self.request(json!({ "method": "scan_file", "path": path.as_str() }))
+        else {
+            return;
+        };
+
+        let path_id = collector.push_path(path.to_owned());
+        self.paths.lock().unwrap().insert(path_id, path.to_owned());
+
+        for dep in deps {
+            let kind = dep.kind.map(|kind| {
+                let internal = format!("{KIND_PREFIX}{}\0{}", self.name, kind.id);
+                let id = collector.get_kind_id(internal, || kind.title);
+                self.kinds.lock().unwrap().insert(id, kind.id);
+                id
+            });
+
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind,
+                name: dep.name,
+                renamed: dep.renamed,
+                registry: dep.registry,
+                protocol: dep.protocol,
+                version: dep.version,
+            });
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let dep = {
+            let paths = self.paths.lock().unwrap();
+            let kinds = self.kinds.lock().unwrap();
+            let path = dep.path.and_then(|id| paths.get(&id));
+            let kind = dep.kind.and_then(|id| kinds.get(&id));
+            self.dep_json(
+                path.map(Utf8PathBuf::as_path),
+                kind.map(String::as_str),
+                dep,
+            )
+        };
+
+        let Some(found) = self.request::<Found>(json!({ "method": "find_updates", "dep": dep }))
+        else {
+            return Updates::Failed;
+        };
+        notes.extend(found.notes);
+        candidates.extend(found.candidates);
+        found.updates
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let message = json!({
+            "method": "apply",
+            "dep": self.resolved_dep_json(deps, dep),
+            "version": to_json(version),
+        });
+        let Some(Applied { edits }) = self.request(message) else {
+            return;
+        };
+
+        let mut by_path = BTreeMap::<_, Vec<_>>::new();
+        for edit in edits {
+            by_path.entry(edit.path.clone()).or_default().push(edit);
+        }
+
+        for (path, mut edits) in by_path {
+            let mut text = fs::read_to_string(&path).unwrap();
+
+            // Apply from the end so earlier offsets stay valid
+            edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+            for edit in edits {
+                let range = edit.start..edit.end;
+                if text.get(range.clone()).is_none() {
+                    log::warn!("{}: invalid edit of {path} at {range:?}", self.name);
+                    continue;
+                }
+                text.replace_range(range, &edit.text);
+            }
+
+            fs::write(&path, text).unwrap();
+        }
+    }
+
+    fn after_apply(&self, deps: &Deps, applied: &[&Dep]) {
+        let applied = applied
+            .iter()
+            .map(|dep| self.resolved_dep_json(deps, dep))
+            .collect::<Vec<_>>();
+        self.request::<Done>(json!({ "method": "after_apply", "deps": applied }));
+    }
+}
+
+impl Drop for Manager {
+    fn drop(&mut self) {
+        let process = self.process.get_mut().unwrap();
+        drop(process.stdin.take());
+        if let Err(err) = process.child.wait() {
+            log::warn!("{}: failed to wait for exit: {err}", self.name);
+        }
+    }
+}
+
+/// Sends one request and reads the response line.
+fn request<T: for<'a> Facet<'a>>(
+    process: &Mutex<Process>,
+    message: serde_json::Value,
+) -> anyhow::Result<T> {
+    let mut process = process.lock().unwrap();
+
+    let mut line = message.to_string();
+    line.push('\n');
+    let stdin = process.stdin.as_mut().unwrap();
+    stdin.write_all(line.as_bytes())?;
+    stdin.flush()?;
+
+    let mut line = String::new();
+    if process.stdout.read_line(&mut line)? == 0 {
+        anyhow::bail!("exited unexpectedly");
+    }
+
+    let response: serde_json::Value = serde_json::from_str(&line).context("invalid response")?;
+    if let Some(error) = response.get("error") {
+        anyhow::bail!("{}", error.as_str().unwrap_or_default());
+    }
+
+    facet_json::from_str(&line).map_err(|err| anyhow::anyhow!("invalid response: {err}"))
+}
+
+fn to_json<T: Facet<'static>>(value: &T) -> serde_json::Value {
+    serde_json::from_str(&facet_json::to_string(value)).unwrap()
+}
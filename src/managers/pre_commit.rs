@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::ops::Range;
+
+use camino::Utf8Path;
+
+use crate::DepCollector;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+use crate::git;
+use crate::yaml::{self, Scalar, Segment};
+
+static CONFIG_FILES: &[&str] = &["**/.pre-commit-config.yaml", "**/.pre-commit-config.yml"];
+/// Comment `pre-commit autoupdate --freeze` leaves after commit revisions
+static FROZEN: &str = "# frozen:";
+
+pub(super) struct Manager;
+
+/// A hook repository pinned with `rev`
+struct HookRepo {
+    url: String,
+    rev: Scalar,
+    /// Tag named by a `# frozen:` comment after a commit, and where it is
+    frozen: Option<(String, Range<usize>)>,
+}
+
+impl HookRepo {
+    fn version(&self) -> Version {
+        let repo = self.url.clone();
+        let rev = self.rev.value.clone();
+        if !git::is_commit(&rev) {
+            return Version::SemVer(rev);
+        }
+
+        match &self.frozen {
+            Some((tag, _)) => Version::GitPinnedTag {
+                repo,
+                commit: rev,
+                tag: tag.clone(),
+            },
+            None => Version::GitCommit { repo, commit: rev },
+        }
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "pre-commit"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(CONFIG_FILES)
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        let path_id = collector.push_path(path.to_owned());
+        let text = fs::read_to_string(path).unwrap();
+
+        let mut seen = HashSet::new();
+        for repo in hook_repos(path, &text) {
+            if !seen.insert((repo.url.clone(), repo.rev.value.clone())) {
+                continue;
+            }
+
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind: None,
+                name: repo.url.clone(),
+                renamed: None,
+                registry: None,
+                protocol: None,
+                version: repo.version(),
+            });
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        _notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        super::find_tag_updates(&dep.name, &dep.version, candidates)
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let current = rev(&dep.version);
+        let tag = match version {
+            Version::GitPinnedTag { tag, .. } => Some(tag),
+            _ => None,
+        };
+
+        let path = deps.path(dep.path.unwrap());
+        let mut text = fs::read_to_string(path).unwrap();
+
+        let found = hook_repos(path, &text)
+            .into_iter()
+            .filter(|repo| repo.url == dep.name && repo.rev.value == current)
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            log::warn!("{path}: could not find {} {}", dep.name, dep.version);
+            return;
+        }
+
+        for repo in found.iter().rev() {
+            // The comment follows the revision, so replace it first
+            if let (Some((_, range)), Some(tag)) = (&repo.frozen, tag) {
+                text.replace_range(range.clone(), tag);
+            }
+            yaml::replace(&mut text, &repo.rev, rev(version));
+        }
+        fs::write(path, text).unwrap();
+    }
+}
+
+/// The value of `rev` for `version`.
+fn rev(version: &Version) -> &str {
+    match version {
+        Version::SemVer(tag) => tag,
+        Version::GitCommit { commit, .. } | Version::GitPinnedTag { commit, .. } => commit,
+        Version::OciPinnedTag { .. } => unreachable!(),
+    }
+}
+
+/// Finds the `repo` and `rev` of each entry of `repos`, skipping `local` and
+/// `meta` repositories which have no revision.
+fn hook_repos(path: &Utf8Path, text: &str) -> Vec<HookRepo> {
+    let scalars = match yaml::scalars(text) {
+        Ok(scalars) => scalars,
+        Err(err) => {
+            log::warn!("{path}: failed to parse: {err}");
+            return Vec::new();
+        }
+    };
+
+    let mut entries = BTreeMap::<usize, (Option<String>, Option<Scalar>)>::new();
+    for (at, scalar) in scalars {
+        let [Segment::Key(repos), Segment::Index(i), Segment::Key(key)] = at.as_slice() else {
+            continue;
+        };
+        if repos != "repos" {
+            continue;
+        }
+
+        let entry = entries.entry(*i).or_default();
+        match key.as_str() {
+            "repo" => entry.0 = Some(scalar.value),
+            "rev" => entry.1 = Some(scalar),
+            _ => {}
+        }
+    }
+
+    entries
+        .into_values()
+        .filter_map(|(url, rev)| {
+            let (url, rev) = (url?, rev?);
+
+            let end = rev.end();
+            let line_end = text[end..].find('\n').map_or(text.len(), |i| end + i);
+            let comment = &text[end..line_end];
+            let frozen = comment.find(FROZEN).map(|start| {
+                let tag_start = start + FROZEN.len();
+                let tag = comment[tag_start..].trim();
+                let tag_start = end + tag_start + comment[tag_start..].find(tag).unwrap();
+                (tag.to_owned(), tag_start..tag_start + tag.len())
+            });
+
+            Some(HookRepo { url, rev, frozen })
+        })
+        .collect()
+}
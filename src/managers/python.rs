@@ -0,0 +1,369 @@
+mod index;
+mod version;
+
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use camino::Utf8Path;
+use taplo::dom::Node;
+use taplo::dom::node::DomNode as _;
+
+use self::version::PyVersion;
+use crate::DepCollector;
+use crate::config;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+
+static PYPROJECT: &str = "pyproject.toml";
+static REQUIREMENTS_KIND: &str = "requirements";
+
+/// Arrays of requirements in `pyproject.toml`, where `*` matches every key
+static PYPROJECT_ARRAYS: &[(&[&str], &str)] = &[
+    (&["project", "dependencies"], "Runtime"),
+    (&["project", "optional-dependencies", "*"], "Optional"),
+    (&["dependency-groups", "*"], "Group"),
+    (&["tool", "uv", "dev-dependencies"], "Dev"),
+    (&["tool", "uv", "constraint-dependencies"], "Constraints"),
+    (&["tool", "uv", "override-dependencies"], "Overrides"),
+];
+
+pub(super) struct Manager {
+    index: index::Index,
+    /// `requires-python` of the project each scanned file belongs to, by path
+    requires_python: Mutex<HashMap<usize, String>>,
+}
+
+impl Manager {
+    pub(super) fn new(config: &config::Python) -> Self {
+        Self {
+            index: index::Index::new(config.index_url.as_deref()),
+            requires_python: Mutex::default(),
+        }
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "Python"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(&["**/pyproject.toml", "**/requirements*.txt"])
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        // Requirements files don't have a fixed name, so track the file itself
+        let path_id = collector.push_path(path.to_owned());
+        let text = fs::read_to_string(path).unwrap();
+
+        if path.file_name() == Some(PYPROJECT) {
+            scan_pyproject(&text, path, path_id, collector);
+        } else {
+            scan_requirements(&text, path_id, collector);
+        }
+
+        if let Some(requires_python) = find_requires_python(path.parent().unwrap()) {
+            let mut all = self.requires_python.lock().unwrap();
+            all.insert(path_id, requires_python);
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let Version::SemVer(current) = &dep.version else {
+            unreachable!()
+        };
+        let Some(current_version) = PyVersion::parse(current) else {
+            log::warn!("could not parse version {current} of {}", dep.name);
+            return Updates::Failed;
+        };
+
+        let Some(releases) = self.index.releases(&dep.name) else {
+            return Updates::Failed;
+        };
+
+        if releases.iter().any(|r| r.raw == *current && r.yanked) {
+            notes.push(format!("{current} has been yanked"));
+        }
+
+        let requires_python = self.requires_python.lock().unwrap();
+        let requires_python = dep.path.and_then(|id| requires_python.get(&id));
+        let python = requires_python.and_then(|spec| version::minimum_python(spec));
+
+        let eligible = releases.iter().filter(|release| {
+            !release.yanked && (current_version.is_prerelease() || !release.version.is_prerelease())
+        });
+        let supported = eligible.clone().filter(|release| {
+            let supports = |python| {
+                release
+                    .requires_python
+                    .as_deref()
+                    .is_none_or(|spec| version::satisfies(python, spec))
+            };
+            python.as_ref().is_none_or(supports)
+        });
+        let newest = eligible.clone().next_back();
+        let latest = supported.clone().next_back();
+
+        if let (Some(newest), Some(requires_python)) = (newest, requires_python)
+            && latest.is_none_or(|latest| latest.version < newest.version)
+            && newest.version > current_version
+        {
+            notes.push(format!(
+                "{} needs Python {}, but `requires-python` allows {requires_python}",
+                newest.raw,
+                newest.requires_python.as_deref().unwrap_or_default(),
+            ));
+        }
+
+        match latest {
+            Some(latest) if latest.version > current_version => {
+                let releases =
+                    supported.map(|release| (release.raw.clone(), release.released.clone()));
+                *candidates = super::candidates(current, releases, Version::SemVer);
+                Updates::Found(Version::SemVer(latest.raw.clone()))
+            }
+            _ => Updates::None,
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let (Version::SemVer(current), Version::SemVer(latest)) = (&dep.version, version) else {
+            unreachable!()
+        };
+
+        let path = deps.path(dep.path.unwrap());
+        let mut text = fs::read_to_string(path).unwrap();
+        let kind = deps.internal_kind(dep.kind.unwrap());
+
+        let matches = |pin: &Pin<'_>| {
+            index::normalize(pin.name) == index::normalize(&dep.name) && pin.version == current
+        };
+
+        let mut replacements = Vec::new();
+        if kind == REQUIREMENTS_KIND {
+            let mut offset = 0;
+            for line in text.split_inclusive('\n') {
+                if let Some(pin) = requirement_line(line)
+                    && matches(&pin)
+                {
+                    replacements.push(offset + pin.span.start..offset + pin.span.end);
+                }
+                offset += line.len();
+            }
+        } else {
+            let dom = taplo::parser::parse(&text).into_dom();
+            let keys = kind.split('\0').collect::<Vec<_>>();
+            if let Some(Node::Array(array)) = get_node(&dom, &keys) {
+                for item in array.items().read().iter() {
+                    let Some(range) = item.syntax().map(|syntax| syntax.text_range()) else {
+                        continue;
+                    };
+                    let range = usize::from(range.start())..usize::from(range.end());
+                    if let Some((inner, pin)) = string_pin(&text[range.clone()])
+                        && matches(&pin)
+                    {
+                        let start = range.start + inner + pin.span.start;
+                        replacements.push(start..start + pin.span.len());
+                    }
+                }
+            }
+        }
+
+        if replacements.is_empty() {
+            log::warn!("{path}: could not find {}=={current}", dep.name);
+            return;
+        }
+
+        for range in replacements.into_iter().rev() {
+            text.replace_range(range, latest);
+        }
+        fs::write(path, text).unwrap();
+    }
+}
+
+fn scan_pyproject(text: &str, path: &Utf8Path, path_id: usize, collector: DepCollector<'_>) {
+    let parsed = taplo::parser::parse(text);
+    if let Some(err) = parsed.errors.first() {
+        log::warn!("{path}: failed to parse: {err}");
+        return;
+    }
+    let dom = parsed.into_dom();
+
+    for (keys, title) in PYPROJECT_ARRAYS {
+        let (parent, wildcard) = match keys.split_last() {
+            Some((&"*", parent)) => (parent, true),
+            _ => (*keys, false),
+        };
+        let Some(node) = get_node(&dom, parent) else {
+            continue;
+        };
+
+        let mut arrays = Vec::new();
+        if wildcard {
+            let Node::Table(table) = node else {
+                log::warn!("{path}: {} is not a table", parent.join("."));
+                continue;
+            };
+            for (group, array) in table.entries().read().iter() {
+                let group = group.value();
+                let mut kind = parent.to_vec();
+                kind.push(group);
+                arrays.push((kind.join("\0"), format!("{title} ({group})"), array.clone()));
+            }
+        } else {
+            arrays.push((parent.join("\0"), (*title).to_owned(), node));
+        }
+
+        for (kind, title, array) in arrays {
+            let Node::Array(array) = array else {
+                log::warn!("{path}: {} is not an array", kind.replace('\0', "."));
+                continue;
+            };
+
+            let kind_id = collector.get_kind_id(kind, || title);
+            for item in array.items().read().iter() {
+                // Tables like `{ include-group = "..." }` aren't requirements
+                let Some(requirement) = item.as_str() else {
+                    continue;
+                };
+                let requirement = requirement.value();
+                match parse_pin(requirement) {
+                    Some(pin) => push_pin(collector, path_id, kind_id, &pin),
+                    None => log::debug!("skipping unpinned requirement {requirement}"),
+                }
+            }
+        }
+    }
+}
+
+fn scan_requirements(text: &str, path_id: usize, collector: DepCollector<'_>) {
+    let kind_id = collector.get_kind_id(REQUIREMENTS_KIND.to_owned(), || "Requirements".to_owned());
+    for line in text.lines() {
+        if let Some(pin) = requirement_line(line) {
+            push_pin(collector, path_id, kind_id, &pin);
+        }
+    }
+}
+
+fn push_pin(collector: DepCollector<'_>, path_id: usize, kind_id: usize, pin: &Pin<'_>) {
+    collector.push_dep(DepInit {
+        path: Some(path_id),
+        kind: Some(kind_id),
+        name: pin.name.to_owned(),
+        renamed: None,
+        registry: None,
+        protocol: None,
+        version: Version::SemVer(pin.version.to_owned()),
+    });
+}
+
+/// Finds `project.requires-python` in the closest `pyproject.toml`.
+fn find_requires_python(dir: &Utf8Path) -> Option<String> {
+    dir.ancestors().find_map(|dir| {
+        let text = fs::read_to_string(dir.join(PYPROJECT)).ok()?;
+        let dom = taplo::parser::parse(&text).into_dom();
+        let node = get_node(&dom, &["project", "requires-python"])?;
+        Some(node.as_str()?.value().to_owned())
+    })
+}
+
+fn get_node(root: &Node, keys: &[&str]) -> Option<Node> {
+    let mut node = root.clone();
+    for key in keys {
+        node = node.as_table()?.get(*key)?;
+    }
+    Some(node)
+}
+
+struct Pin<'a> {
+    name: &'a str,
+    version: &'a str,
+    /// Byte range of the version within the text it was parsed from
+    span: Range<usize>,
+}
+
+/// Parses a requirement pinned to a single version, like
+/// `name[extra]==1.2.3 ; python_version < "3.11"`.
+fn parse_pin(requirement: &str) -> Option<Pin<'_>> {
+    let trimmed = requirement
+        .trim_start()
+        .split(';')
+        .next()
+        .unwrap()
+        .trim_end();
+
+    let name_len = trimmed
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(trimmed.len());
+    if name_len == 0 {
+        return None;
+    }
+    let name = &trimmed[..name_len];
+
+    let mut rest = trimmed[name_len..].trim_start();
+    if let Some(extras) = rest.strip_prefix('[') {
+        rest = extras.split_once(']')?.1.trim_start();
+    }
+
+    let specifier = rest
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+        .unwrap_or(rest)
+        .trim();
+    let version = specifier
+        .strip_prefix("===")
+        .or_else(|| specifier.strip_prefix("=="))?
+        .trim_start();
+
+    let invalid = |c: char| c.is_whitespace() || matches!(c, ',' | '*' | '@');
+    if version.is_empty() || version.contains(invalid) {
+        return None;
+    }
+
+    let start = version.as_ptr().addr() - requirement.as_ptr().addr();
+    Some(Pin {
+        name,
+        version,
+        span: start..start + version.len(),
+    })
+}
+
+/// Parses one line of a requirements file, skipping options and hash-checked
+/// requirements, which need to be regenerated instead.
+fn requirement_line(line: &str) -> Option<Pin<'_>> {
+    let requirement = line.trim_end();
+    let requirement = match requirement.find(" #") {
+        Some(comment) => &requirement[..comment],
+        None => requirement,
+    };
+
+    let trimmed = requirement.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with(['#', '-']) {
+        return None;
+    }
+
+    if requirement.contains("--hash") || requirement.ends_with('\\') {
+        log::info!("skipping hash-checked requirement {trimmed}");
+        return None;
+    }
+
+    parse_pin(requirement)
+}
+
+/// Parses a TOML string literal holding a requirement, returning where its
+/// contents start along with the pin.
+fn string_pin(literal: &str) -> Option<(usize, Pin<'_>)> {
+    let quotes = if literal.starts_with("\"\"\"") || literal.starts_with("'''") {
+        3
+    } else {
+        1
+    };
+    let inner = literal.get(quotes..literal.len().checked_sub(quotes)?)?;
+    Some((quotes, parse_pin(inner)?))
+}
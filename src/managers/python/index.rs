@@ -0,0 +1,183 @@
+//! Lists releases from a package index through the simple repository API,
+//! preferring its JSON form (PEP 691) over HTML (PEP 503).
+
+use std::collections::BTreeMap;
+use std::env;
+
+use super::version::PyVersion;
+
+static DEFAULT_INDEX: &str = "https://pypi.org/simple/";
+static JSON_TYPE: &str = "application/vnd.pypi.simple.v1+json";
+
+pub(super) struct Index {
+    url: String,
+}
+
+pub(super) struct Release {
+    pub(super) raw: String,
+    pub(super) version: PyVersion,
+    pub(super) requires_python: Option<String>,
+    /// Whether every file of the release is yanked
+    pub(super) yanked: bool,
+    /// When the first file was uploaded, only known from JSON indexes
+    pub(super) released: Option<String>,
+}
+
+struct File {
+    filename: String,
+    requires_python: Option<String>,
+    yanked: bool,
+    uploaded: Option<String>,
+}
+
+impl Index {
+    /// Uses the configured index, then `PIP_INDEX_URL` or `UV_INDEX_URL`,
+    /// then PyPI.
+    pub(super) fn new(configured: Option<&str>) -> Self {
+        let url = configured
+            .map(str::to_owned)
+            .or_else(|| env::var("PIP_INDEX_URL").ok())
+            .or_else(|| env::var("UV_INDEX_URL").ok())
+            .unwrap_or_else(|| DEFAULT_INDEX.to_owned());
+        let url = url.trim_end_matches('/').to_owned();
+        Self { url }
+    }
+
+    pub(super) fn releases(&self, name: &str) -> Option<Vec<Release>> {
+        let url = format!("{}/{}/", self.url, normalize(name));
+        let mut response = match ureq::get(&url)
+            .header("Accept", format!("{JSON_TYPE}, text/html;q=0.1"))
+            .call()
+        {
+            Ok(response) => response,
+            Err(err) => {
+                log::warn!("failed to fetch {name} from {}: {err}", self.url);
+                return None;
+            }
+        };
+
+        let is_json = response
+            .headers()
+            .get("Content-Type")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("json"));
+        let body = response.body_mut().read_to_string().ok()?;
+        let files = if is_json {
+            parse_json(&body)?
+        } else {
+            parse_html(&body)
+        };
+
+        let mut releases = BTreeMap::<String, Release>::new();
+        for file in files {
+            let Some(raw) = file_version(&file.filename) else {
+                continue;
+            };
+            let Some(version) = PyVersion::parse(raw) else {
+                continue;
+            };
+
+            let release = releases.entry(raw.to_owned()).or_insert_with(|| Release {
+                raw: raw.to_owned(),
+                version,
+                requires_python: None,
+                yanked: true,
+                released: None,
+            });
+            release.yanked &= file.yanked;
+            // Timestamps in the same format sort by time
+            if let Some(uploaded) = file.uploaded
+                && release.released.as_ref().is_none_or(|r| uploaded < *r)
+            {
+                release.released = Some(uploaded);
+            }
+            if release.requires_python.is_none() {
+                release.requires_python = file.requires_python;
+            }
+        }
+
+        let mut releases = releases.into_values().collect::<Vec<_>>();
+        releases.sort_by(|a, b| a.version.cmp(&b.version));
+        Some(releases)
+    }
+}
+
+/// Normalizes a project name as in PEP 503.
+pub(super) fn normalize(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !out.ends_with('-') {
+                out.push('-');
+            }
+        } else {
+            out.push(c.to_ascii_lowercase());
+        }
+    }
+    out
+}
+
+fn parse_json(body: &str) -> Option<Vec<File>> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let files = json["files"].as_array()?;
+    let files = files
+        .iter()
+        .filter_map(|file| {
+            Some(File {
+                filename: file["filename"].as_str()?.to_owned(),
+                requires_python: file["requires-python"].as_str().map(str::to_owned),
+                // Either `true` or the reason it was yanked
+                yanked: file["yanked"]
+                    .as_bool()
+                    .unwrap_or(file["yanked"].is_string()),
+                uploaded: file["upload-time"].as_str().map(str::to_owned),
+            })
+        })
+        .collect();
+    Some(files)
+}
+
+fn parse_html(body: &str) -> Vec<File> {
+    let mut files = Vec::new();
+    for anchor in body.split("<a ").skip(1) {
+        let Some((attributes, rest)) = anchor.split_once('>') else {
+            continue;
+        };
+        let Some((filename, _)) = rest.split_once("</a>") else {
+            continue;
+        };
+
+        let requires_python = attributes
+            .split_once("data-requires-python=\"")
+            .and_then(|(_, value)| value.split_once('"'))
+            .map(|(value, _)| unescape(value));
+
+        files.push(File {
+            filename: unescape(filename.trim()),
+            requires_python,
+            yanked: attributes.contains("data-yanked"),
+            uploaded: None,
+        });
+    }
+    files
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Extracts the version from a wheel or source distribution filename.
+fn file_version(filename: &str) -> Option<&str> {
+    if let Some(stem) = filename.strip_suffix(".whl") {
+        return stem.split('-').nth(1);
+    }
+
+    let stem = [".tar.gz", ".tar.bz2", ".tar.xz", ".zip", ".tgz"]
+        .into_iter()
+        .find_map(|extension| filename.strip_suffix(extension))?;
+    Some(stem.rsplit_once('-')?.1)
+}
@@ -0,0 +1,174 @@
+//! Enough of PEP 440 to order releases and check `requires-python`.
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct PyVersion {
+    epoch: u64,
+    release: Vec<u64>,
+    /// `(a | b | rc, number)`, with the phase as 0, 1, or 2
+    pre: Option<(u8, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+}
+
+impl PyVersion {
+    pub(super) fn parse(version: &str) -> Option<Self> {
+        let version = version.trim().to_lowercase();
+        let version = version.strip_prefix('v').unwrap_or(&version);
+        // Local versions never appear on an index
+        let version = version.split('+').next().unwrap();
+
+        let (epoch, version) = match version.split_once('!') {
+            Some((epoch, rest)) => (epoch.parse().ok()?, rest),
+            None => (0, version),
+        };
+
+        let release_end = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+        let release = version[..release_end]
+            .trim_end_matches('.')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+
+        let mut parsed = Self {
+            epoch,
+            release,
+            pre: None,
+            post: None,
+            dev: None,
+        };
+
+        let mut rest = &version[release_end..];
+        while !rest.is_empty() {
+            rest = rest.trim_start_matches(['.', '-', '_']);
+            let label_end = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let (label, after) = rest.split_at(label_end);
+            let number_end = after
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(after.len());
+            let number = after[..number_end].parse().unwrap_or(0);
+            rest = after[number_end..].trim_start_matches(['.', '-', '_']);
+
+            match label {
+                "a" | "alpha" => parsed.pre = Some((0, number)),
+                "b" | "beta" => parsed.pre = Some((1, number)),
+                "rc" | "c" | "pre" | "preview" => parsed.pre = Some((2, number)),
+                "post" | "rev" | "r" => parsed.post = Some(number),
+                // `1.0-1` is an implicit post release
+                "" if number_end > 0 => parsed.post = Some(number),
+                "dev" => parsed.dev = Some(number),
+                _ => return None,
+            }
+        }
+
+        Some(parsed)
+    }
+
+    pub(super) fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    fn release_cmp(&self, other: &[u64]) -> Ordering {
+        let len = self.release.len().max(other.len());
+        let pad = |release: &[u64]| {
+            let mut release = release.to_vec();
+            release.resize(len, 0);
+            release
+        };
+        pad(&self.release).cmp(&pad(other))
+    }
+
+    fn starts_with(&self, prefix: &[u64]) -> bool {
+        let mut release = self.release.clone();
+        if release.len() < prefix.len() {
+            release.resize(prefix.len(), 0);
+        }
+        release.starts_with(prefix)
+    }
+}
+
+impl Ord for PyVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Dev releases of a final release sort before its pre-releases
+        let phase = |v: &Self| match (v.pre, v.post, v.dev) {
+            (None, None, Some(_)) => (0, 0, 0),
+            (Some((phase, number)), _, _) => (1, phase, number),
+            _ => (2, 0, 0),
+        };
+        let post = |v: &Self| v.post.map_or(0, |n| n + 1);
+        let dev = |v: &Self| v.dev.unwrap_or(u64::MAX);
+
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.release_cmp(&other.release))
+            .then_with(|| phase(self).cmp(&phase(other)))
+            .then_with(|| post(self).cmp(&post(other)))
+            .then_with(|| dev(self).cmp(&dev(other)))
+    }
+}
+
+impl PartialOrd for PyVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Whether `version` satisfies every clause of a specifier like
+/// `>=3.8, !=3.9.*`. Unparseable clauses are ignored.
+pub(super) fn satisfies(version: &PyVersion, specifier: &str) -> bool {
+    specifier
+        .split(',')
+        .map(str::trim)
+        .filter(|clause| !clause.is_empty())
+        .all(|clause| satisfies_clause(version, clause).unwrap_or(true))
+}
+
+fn satisfies_clause(version: &PyVersion, clause: &str) -> Option<bool> {
+    let operator_end = clause
+        .find(|c: char| c.is_ascii_alphanumeric())
+        .unwrap_or(clause.len());
+    let (operator, bound) = clause.split_at(operator_end);
+    let bound = bound.trim();
+
+    if let Some(prefix) = bound.strip_suffix(".*") {
+        let prefix = PyVersion::parse(prefix)?.release;
+        return match operator.trim() {
+            "==" => Some(version.starts_with(&prefix)),
+            "!=" => Some(!version.starts_with(&prefix)),
+            _ => None,
+        };
+    }
+
+    let bound = PyVersion::parse(bound)?;
+    let ordering = version.cmp(&bound);
+    Some(match operator.trim() {
+        ">=" => ordering.is_ge(),
+        ">" => ordering.is_gt(),
+        "<=" => ordering.is_le(),
+        "<" => ordering.is_lt(),
+        "==" | "===" => ordering.is_eq(),
+        "!=" => ordering.is_ne(),
+        "~=" => {
+            let prefix = &bound.release[..bound.release.len().saturating_sub(1)];
+            ordering.is_ge() && version.starts_with(prefix)
+        }
+        _ => return None,
+    })
+}
+
+/// The oldest Python a project's `requires-python` allows, eg `3.10` for
+/// `>=3.10,<4`.
+pub(super) fn minimum_python(requires_python: &str) -> Option<PyVersion> {
+    requires_python.split(',').find_map(|clause| {
+        let clause = clause.trim();
+        let bound = ["~=", ">=", "=="]
+            .into_iter()
+            .find_map(|operator| clause.strip_prefix(operator))?;
+        PyVersion::parse(bound.trim().trim_end_matches(".*"))
+    })
+}
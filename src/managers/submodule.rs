@@ -0,0 +1,185 @@
+use std::collections::{BTreeSet, HashMap};
+
+use camino::Utf8Path;
+use gix::submodule::config::Branch;
+
+use crate::DepCollector;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+use crate::git;
+
+/// Prefix of the protocol recording the branch a submodule tracks, which is
+/// left out for the remote's default branch
+static BRANCH_PROTOCOL: &str = "branch:";
+
+pub(super) struct Manager;
+
+/// The repository a `.gitmodules` file belongs to.
+fn superproject(gitmodules: &Utf8Path) -> &Utf8Path {
+    gitmodules
+        .parent()
+        .filter(|dir| !dir.as_str().is_empty())
+        .unwrap_or(Utf8Path::new("."))
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "Git submodule"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(&["**/.gitmodules"])
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        let repo = match gix::open(superproject(path)) {
+            Ok(repo) => repo,
+            Err(err) => {
+                log::warn!("{path}: failed to open repository: {err}");
+                return;
+            }
+        };
+        let submodules = match repo.submodules() {
+            Ok(Some(submodules)) => submodules,
+            Ok(None) => return,
+            Err(err) => {
+                log::warn!("{path}: failed to parse: {err}");
+                return;
+            }
+        };
+
+        let path_id = collector.push_path(path.to_owned());
+        for submodule in submodules {
+            let name = submodule.name();
+            let (Ok(submodule_path), Ok(url)) = (submodule.path(), submodule.url()) else {
+                log::warn!("{path}: skipping invalid submodule {name}");
+                continue;
+            };
+            // Submodules only listed in `.gitmodules` have no commit to update
+            let Ok(Some(commit)) = submodule.index_id() else {
+                log::debug!("{path}: skipping submodule {name} missing from the index");
+                continue;
+            };
+
+            let branch = match submodule.branch() {
+                Ok(Some(Branch::Name(branch))) => Some(branch.to_string()),
+                Ok(Some(Branch::CurrentInSuperproject)) => repo
+                    .head_name()
+                    .ok()
+                    .flatten()
+                    .map(|head| head.shorten().to_string()),
+                _ => None,
+            };
+
+            // Commits pinned to a tag follow newer tags instead of a branch
+            let repo_url = url.to_bstring().to_string();
+            let commit = commit.to_string();
+            let version = match git::commit_tag(&git::open_repo(&repo_url), &commit) {
+                Some(tag) => Version::GitPinnedTag {
+                    repo: repo_url,
+                    commit,
+                    tag,
+                },
+                None => Version::GitCommit {
+                    repo: repo_url,
+                    commit,
+                },
+            };
+
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind: None,
+                name: submodule_path.to_string(),
+                renamed: None,
+                registry: None,
+                protocol: branch.map(|branch| format!("{BRANCH_PROTOCOL}{branch}")),
+                version,
+            });
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        _notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let (repo, commit) = match &dep.version {
+            Version::GitPinnedTag { repo, .. } => {
+                return super::find_tag_updates(repo, &dep.version, candidates);
+            }
+            Version::GitCommit { repo, commit } => (repo, commit),
+            _ => unreachable!(),
+        };
+        let upstream = git::open_repo(repo);
+
+        let branch = dep
+            .protocol
+            .as_deref()
+            .and_then(|protocol| protocol.strip_prefix(BRANCH_PROTOCOL));
+        let latest = match branch {
+            Some(branch) => git::branch(&upstream, branch),
+            None => git::default_branch(&upstream),
+        };
+        let Some(latest) = latest else {
+            let branch = branch.unwrap_or("the default branch");
+            log::warn!("could not find {branch} of {repo}");
+            return Updates::Failed;
+        };
+
+        let latest = hex::encode(latest.id.as_slice());
+        if latest == *commit {
+            Updates::None
+        } else {
+            Updates::Found(Version::GitCommit {
+                repo: repo.clone(),
+                commit: latest,
+            })
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let (Version::GitCommit { commit, .. } | Version::GitPinnedTag { commit, .. }) = version
+        else {
+            unreachable!()
+        };
+
+        let gitmodules = deps.path(dep.path.unwrap());
+        let repo = gix::open(superproject(gitmodules)).unwrap();
+        let mut index = repo.open_index().unwrap();
+
+        let stage = gix::index::entry::Stage::Unconflicted;
+        let Some(entry) = index.entry_mut_by_path_and_stage(dep.name.as_str().into(), stage) else {
+            log::warn!(
+                "{gitmodules}: could not find submodule {} in the index",
+                dep.name
+            );
+            return;
+        };
+        entry.id = gix::ObjectId::from_hex(commit.as_bytes()).unwrap();
+        index.write(Default::default()).unwrap();
+    }
+
+    fn after_apply(&self, deps: &Deps, applied: &[&Dep]) {
+        let mut by_superproject = HashMap::<_, BTreeSet<_>>::new();
+        for dep in applied {
+            let gitmodules = deps.path(dep.path.unwrap());
+            by_superproject
+                .entry(superproject(gitmodules))
+                .or_default()
+                .insert(dep.name.as_str());
+        }
+
+        // Check out the new commits of submodules that are already initialized
+        for (dir, paths) in by_superproject {
+            let args = ["submodule", "update", "--"].into_iter().chain(paths);
+            if let Err(err) = duct::cmd("git", args)
+                .dir(dir)
+                .stdin_null()
+                .stdout_null()
+                .run()
+            {
+                log::warn!("{dir}: failed to update submodules: {err}");
+            }
+        }
+    }
+}
@@ -0,0 +1,327 @@
+mod registry;
+
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+
+use camino::Utf8Path;
+use hcl_edit::Span as _;
+use hcl_edit::expr::{Expression, ObjectKey};
+use hcl_edit::structure::Body;
+
+use crate::DepCollector;
+use crate::config;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+use crate::git;
+
+/// Internal and display names of provider and module pins
+static PROVIDER_KIND: (&str, &str) = ("provider", "Provider");
+static MODULE_KIND: (&str, &str) = ("module", "Module");
+static LOCK_FILE: &str = ".terraform.lock.hcl";
+/// Protocols marking git modules, and providers with hashes in a lock file
+static MODULE_PROTOCOL: &str = "git::";
+static LOCKED_PROTOCOL: &str = "locked:";
+
+pub(super) struct Manager {
+    registry: registry::Registry,
+}
+
+/// A provider or module pin, and the string it is in
+struct Found {
+    kind: (&'static str, &'static str),
+    /// Provider source or repository URL
+    name: String,
+    /// Version or git ref
+    version: String,
+    /// Byte range of the string literal, including the quotes
+    span: Range<usize>,
+}
+
+impl Manager {
+    pub(super) fn new(config: &config::Terraform) -> Self {
+        Self {
+            registry: registry::Registry::new(config.registry.as_deref()),
+        }
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "Terraform"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(&["**/*.tf", "**/*.tofu"])
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        // Modules are split across any number of files, so track each one
+        let path_id = collector.push_path(path.to_owned());
+        let text = fs::read_to_string(path).unwrap();
+        let locked = locked_providers(&path.with_file_name(LOCK_FILE));
+
+        let mut seen = HashSet::new();
+        for found in find_pins(path, &text) {
+            if !seen.insert((found.kind.0, found.name.clone(), found.version.clone())) {
+                continue;
+            }
+
+            let version = if found.kind == MODULE_KIND && git::is_commit(&found.version) {
+                Version::GitCommit {
+                    repo: found.name.clone(),
+                    commit: found.version,
+                }
+            } else {
+                Version::SemVer(found.version)
+            };
+
+            let protocol = if found.kind == MODULE_KIND {
+                Some(MODULE_PROTOCOL.to_owned())
+            } else if locked
+                .iter()
+                .any(|address| same_provider(address, &found.name))
+            {
+                Some(LOCKED_PROTOCOL.to_owned())
+            } else {
+                None
+            };
+
+            let (kind, title) = found.kind;
+            let kind_id = collector.get_kind_id(kind.to_owned(), || title.to_owned());
+
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind: Some(kind_id),
+                name: found.name,
+                renamed: None,
+                registry: None,
+                protocol,
+                version,
+            });
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        if dep.protocol.as_deref() == Some(MODULE_PROTOCOL) {
+            return super::find_tag_updates(&dep.name, &dep.version, candidates);
+        }
+        let Version::SemVer(current) = &dep.version else {
+            unreachable!()
+        };
+
+        let Some(versions) = self.registry.versions(&dep.name) else {
+            return Updates::Failed;
+        };
+        let releases = versions.iter().map(|version| (version.clone(), None));
+        *candidates = super::candidates(current, releases, Version::SemVer);
+        let Some(latest) = super::newest(current, &versions) else {
+            return Updates::None;
+        };
+
+        if dep.protocol.as_deref() == Some(LOCKED_PROTOCOL) {
+            notes.push(format!(
+                "`{LOCK_FILE}` has hashes for {current}; run `terraform init -upgrade` to regenerate them"
+            ));
+        }
+        Updates::Found(Version::SemVer(latest))
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let current = match &dep.version {
+            Version::SemVer(version) => version,
+            Version::GitCommit { commit, .. } => commit,
+            _ => unreachable!(),
+        };
+        let latest = match version {
+            Version::SemVer(version) => version,
+            Version::GitCommit { commit, .. } => commit,
+            _ => unreachable!(),
+        };
+
+        let path = deps.path(dep.path.unwrap());
+        let mut text = fs::read_to_string(path).unwrap();
+        let kind = deps.internal_kind(dep.kind.unwrap());
+
+        let found = find_pins(path, &text)
+            .into_iter()
+            .filter(|found| {
+                found.kind.0 == kind && found.name == dep.name && found.version == *current
+            })
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            log::warn!("{path}: could not find {} {current}", dep.name);
+            return;
+        }
+
+        let (from, to) = if kind == MODULE_KIND.0 {
+            (format!("ref={current}"), format!("ref={latest}"))
+        } else {
+            (current.clone(), latest.clone())
+        };
+        for found in found.iter().rev() {
+            let literal = text[found.span.clone()].replacen(&from, &to, 1);
+            text.replace_range(found.span.clone(), &literal);
+        }
+        fs::write(path, text).unwrap();
+    }
+}
+
+/// Finds exact `required_providers` versions and git module refs.
+fn find_pins(path: &Utf8Path, text: &str) -> Vec<Found> {
+    let body = match hcl_edit::parser::parse_body(text) {
+        Ok(body) => body,
+        Err(err) => {
+            log::warn!("{path}: failed to parse: {err}");
+            return Vec::new();
+        }
+    };
+
+    let mut found = Vec::new();
+    for terraform in body.get_blocks("terraform") {
+        for required in terraform.body.get_blocks("required_providers") {
+            found.extend(required_providers(path, &required.body));
+        }
+    }
+
+    for module in body.get_blocks("module") {
+        let Some(Expression::String(source)) =
+            module.body.get_attribute("source").map(|attr| &attr.value)
+        else {
+            continue;
+        };
+        let Some((url, reference)) = git_source(source) else {
+            continue;
+        };
+
+        found.push(Found {
+            kind: MODULE_KIND,
+            name: url,
+            version: reference.to_owned(),
+            span: source.span().unwrap(),
+        });
+    }
+
+    found
+}
+
+fn required_providers(path: &Utf8Path, body: &Body) -> Vec<Found> {
+    let mut found = Vec::new();
+    for attribute in body.attributes() {
+        let name = attribute.key.as_str();
+        let (source, constraint) = match &attribute.value {
+            // Shorthand from before `source` existed
+            Expression::String(constraint) => (None, constraint),
+            Expression::Object(object) => {
+                let get = |key: &str| {
+                    object.iter().find_map(|(k, v)| {
+                        let k = match k {
+                            ObjectKey::Ident(ident) => ident.as_str(),
+                            ObjectKey::Expression(Expression::String(string)) => string.as_str(),
+                            ObjectKey::Expression(_) => return None,
+                        };
+                        (k == key).then(|| v.expr())
+                    })
+                };
+
+                let source = match get("source") {
+                    Some(Expression::String(source)) => Some(source.as_str()),
+                    _ => None,
+                };
+                let Some(Expression::String(constraint)) = get("version") else {
+                    continue;
+                };
+                (source, constraint)
+            }
+            _ => continue,
+        };
+
+        let Some(version) = exact_version(constraint) else {
+            log::debug!("{path}: skipping provider {name} {}", constraint.as_str());
+            continue;
+        };
+
+        found.push(Found {
+            kind: PROVIDER_KIND,
+            name: source.map_or_else(|| format!("hashicorp/{name}"), str::to_owned),
+            version: version.to_owned(),
+            span: constraint.span().unwrap(),
+        });
+    }
+    found
+}
+
+/// Reads version constraints pinning one version, like `5.31.0` or
+/// `= 5.31.0`.
+fn exact_version(constraint: &str) -> Option<&str> {
+    let version = constraint.trim();
+    let version = version.strip_prefix('=').unwrap_or(version).trim_start();
+    let is_exact = version.split('.').count() == 3
+        && version
+            .split('.')
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    is_exact.then_some(version)
+}
+
+/// Splits a git module source like
+/// `git::https://example.com/repo.git//modules/vpc?ref=v1.2.0` into the
+/// repository URL and the ref.
+fn git_source(source: &str) -> Option<(String, &str)> {
+    let (address, query) = source.split_once('?')?;
+    let reference = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("ref="))?;
+
+    let url = if let Some(url) = address.strip_prefix("git::") {
+        url
+    } else if address.starts_with("github.com/") {
+        address
+    } else {
+        return None;
+    };
+
+    // Drop the subdirectory, which follows a `//` after the scheme
+    let scheme_end = url.find("://").map_or(0, |i| i + 3);
+    let repo_end = url[scheme_end..]
+        .find("//")
+        .map_or(url.len(), |i| scheme_end + i);
+    let url = &url[..repo_end];
+
+    let url = match url.strip_prefix("github.com/") {
+        Some(repo) => format!("https://github.com/{repo}"),
+        None => url.to_owned(),
+    };
+    Some((url, reference))
+}
+
+/// Reads the addresses of the providers in a lock file, like
+/// `registry.terraform.io/hashicorp/aws`.
+fn locked_providers(path: &Utf8Path) -> Vec<String> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let body = match hcl_edit::parser::parse_body(&text) {
+        Ok(body) => body,
+        Err(err) => {
+            log::warn!("{path}: failed to parse: {err}");
+            return Vec::new();
+        }
+    };
+
+    body.get_blocks("provider")
+        .filter_map(|block| Some(block.labels.first()?.as_str().to_owned()))
+        .collect()
+}
+
+/// Whether a lock file address refers to `source`, which may leave out the
+/// registry host.
+fn same_provider(address: &str, source: &str) -> bool {
+    let address = address.to_lowercase();
+    let source = source.to_lowercase();
+    address == source
+        || (source.matches('/').count() == 1 && address.ends_with(&format!("/{source}")))
+}
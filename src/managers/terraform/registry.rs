@@ -0,0 +1,87 @@
+//! Client for the provider registry protocol, as served by
+//! registry.terraform.io and registry.opentofu.org.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub(super) static DEFAULT_HOST: &str = "registry.terraform.io";
+
+pub(super) struct Registry {
+    /// Base URL of the registry for sources without a hostname
+    default: String,
+    /// Providers API of each registry, found through service discovery
+    services: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl Registry {
+    pub(super) fn new(configured: Option<&str>) -> Self {
+        let default = configured.map_or_else(
+            || format!("https://{DEFAULT_HOST}"),
+            |url| url.trim_end_matches('/').to_owned(),
+        );
+        Self {
+            default,
+            services: Mutex::default(),
+        }
+    }
+
+    /// Lists the versions of a provider like `hashicorp/aws` or
+    /// `registry.terraform.io/hashicorp/aws`.
+    pub(super) fn versions(&self, source: &str) -> Option<Vec<String>> {
+        let parts = source.split('/').collect::<Vec<_>>();
+        let (base, namespace, name) = match parts.as_slice() {
+            [namespace, name] => (self.default.clone(), namespace, name),
+            [host, namespace, name] if host.eq_ignore_ascii_case(DEFAULT_HOST) => {
+                (self.default.clone(), namespace, name)
+            }
+            [host, namespace, name] => (format!("https://{host}"), namespace, name),
+            _ => {
+                log::warn!("invalid provider source {source}");
+                return None;
+            }
+        };
+
+        let providers = self.providers_api(&base)?;
+        let url = format!("{providers}{namespace}/{name}/versions");
+        let body = get(&url)?;
+        let body: serde_json::Value = serde_json::from_str(&body).ok()?;
+        let versions = body["versions"]
+            .as_array()?
+            .iter()
+            .filter_map(|version| Some(version["version"].as_str()?.to_owned()))
+            .collect();
+        Some(versions)
+    }
+
+    fn providers_api(&self, base: &str) -> Option<String> {
+        let mut services = self.services.lock().unwrap();
+        services
+            .entry(base.to_owned())
+            .or_insert_with(|| {
+                let body = get(&format!("{base}/.well-known/terraform.json"))?;
+                let body: serde_json::Value = serde_json::from_str(&body).ok()?;
+                let Some(providers) = body["providers.v1"].as_str() else {
+                    log::warn!("{base} does not serve the provider registry protocol");
+                    return None;
+                };
+
+                let providers = if providers.contains("://") {
+                    providers.to_owned()
+                } else {
+                    format!("{base}/{}", providers.trim_start_matches('/'))
+                };
+                Some(format!("{}/", providers.trim_end_matches('/')))
+            })
+            .clone()
+    }
+}
+
+fn get(url: &str) -> Option<String> {
+    match ureq::get(url).call() {
+        Ok(mut response) => response.body_mut().read_to_string().ok(),
+        Err(err) => {
+            log::warn!("failed to fetch {url}: {err}");
+            None
+        }
+    }
+}
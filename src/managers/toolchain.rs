@@ -0,0 +1,405 @@
+pub(super) mod dist;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::sync::{Mutex, OnceLock};
+
+use camino::Utf8Path;
+use facet::Facet;
+use taplo::dom::Node;
+use taplo::dom::node::DomNode as _;
+
+use super::cargo::registry::Registries;
+use crate::DepCollector;
+use crate::config;
+use crate::dep_collector::{Candidate, Dep, DepInit, Deps, Updates, Version};
+use crate::yaml::{self, Scalar, Segment};
+
+static TOOLCHAIN_FILES: &[&str] = &["**/rust-toolchain", "**/rust-toolchain.toml"];
+static RUST: &str = "rust";
+static CHANNEL_KIND: &str = "channel";
+static TOOL_KIND: &str = "tool";
+static INSTALL_ACTION: &str = "taiki-e/install-action@";
+/// How far back to look for a nightly that has every required component
+static NIGHTLY_SEARCH_DAYS: usize = 30;
+
+pub(super) struct Manager {
+    dist: dist::Server,
+    registries: OnceLock<Registries>,
+    /// Components and targets each toolchain file requires, by path
+    requirements: Mutex<HashMap<usize, Requirements>>,
+}
+
+#[derive(Default, Clone)]
+struct Requirements {
+    components: Vec<String>,
+    targets: Vec<String>,
+}
+
+#[derive(Facet)]
+struct Release {
+    vers: String,
+    #[facet(default)]
+    yanked: bool,
+}
+
+impl Manager {
+    pub(super) fn new(config: &config::Rust) -> Self {
+        Self {
+            dist: dist::Server::new(config.dist_server.as_deref()),
+            registries: OnceLock::new(),
+            requirements: Mutex::default(),
+        }
+    }
+
+    fn find_channel_updates(&self, dep: &Dep, current: &str, notes: &mut Vec<String>) -> Updates {
+        let requirements = self.requirements.lock().unwrap();
+        let requirements = dep
+            .path
+            .and_then(|id| requirements.get(&id))
+            .cloned()
+            .unwrap_or_default();
+        let missing = |manifest: &dist::Manifest| {
+            manifest.missing(&requirements.components, &requirements.targets)
+        };
+
+        match Channel::parse(current) {
+            Some(Channel::Release(current)) => {
+                let Some(manifest) = self.dist.manifest("stable", None) else {
+                    return Updates::Failed;
+                };
+                let Some(latest) = manifest.version.as_deref().and_then(|v| newer(current, v))
+                else {
+                    return Updates::None;
+                };
+
+                let missing = missing(&manifest);
+                if missing.is_empty() {
+                    Updates::Found(Version::SemVer(latest))
+                } else {
+                    notes.push(format!("{latest} is missing {}", missing.join(", ")));
+                    Updates::None
+                }
+            }
+            Some(Channel::Dated { channel, date }) => {
+                let Some(latest) = self.dist.manifest(channel, None) else {
+                    return Updates::Failed;
+                };
+
+                let newest = latest.date.clone();
+                let mut day = newest.clone();
+                let mut manifest = Some(latest);
+                for _ in 0..NIGHTLY_SEARCH_DAYS {
+                    if *day <= *date {
+                        break;
+                    }
+
+                    if let Some(manifest) = &manifest {
+                        let missing = missing(manifest);
+                        if day == newest && !missing.is_empty() {
+                            notes.push(format!(
+                                "{channel}-{newest} is missing {}",
+                                missing.join(", ")
+                            ));
+                        }
+                        if missing.is_empty() {
+                            return Updates::Found(Version::SemVer(format!("{channel}-{day}")));
+                        }
+                    }
+
+                    let Some(previous) = dist::previous_day(&day) else {
+                        break;
+                    };
+                    day = previous;
+                    manifest = self.dist.manifest(channel, Some(&day));
+                }
+
+                Updates::None
+            }
+            None => unreachable!(),
+        }
+    }
+
+    fn find_tool_updates(
+        &self,
+        dep: &Dep,
+        current: &str,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let registries = self.registries.get_or_init(Registries::load);
+        let Some(index) = registries.resolve(None) else {
+            return Updates::Failed;
+        };
+        let Some(index) = index.fetch(&dep.name) else {
+            return Updates::Failed;
+        };
+
+        let releases = index
+            .lines()
+            .filter_map(|line| facet_json::from_str::<Release>(line).ok())
+            .filter(|release| !release.yanked)
+            .map(|release| release.vers)
+            .collect::<Vec<_>>();
+        *candidates = super::newer(current, &releases);
+
+        let latest = releases
+            .iter()
+            .filter_map(|release| Some((release_parts(release)?, release)))
+            .max_by(|(a, _), (b, _)| a.cmp(b));
+
+        match latest.and_then(|(_, latest)| newer(current, latest)) {
+            Some(latest) => Updates::Found(Version::SemVer(latest)),
+            None => Updates::None,
+        }
+    }
+}
+
+impl super::Manager for Manager {
+    fn name(&self) -> &'static str {
+        "Toolchain"
+    }
+
+    fn interest(&self) -> super::Interest {
+        super::Interest::files(TOOLCHAIN_FILES).and_github_yaml()
+    }
+
+    fn scan_file(&self, path: &Utf8Path, collector: DepCollector<'_>) {
+        // Workflows don't have a fixed name, so track the file itself
+        let path_id = collector.push_path(path.to_owned());
+        let text = fs::read_to_string(path).unwrap();
+
+        let push = |kind: &str, title: &str, name: &str, version: &str| {
+            collector.push_dep(DepInit {
+                path: Some(path_id),
+                kind: Some(collector.get_kind_id(kind.to_owned(), || title.to_owned())),
+                name: name.to_owned(),
+                renamed: None,
+                registry: None,
+                protocol: None,
+                version: Version::SemVer(version.to_owned()),
+            });
+        };
+
+        if !super::is_github_yaml(path) {
+            let Some((channel, _, requirements)) = read_toolchain(&text) else {
+                log::debug!("{path}: no channel to update");
+                return;
+            };
+
+            if Channel::parse(&channel).is_some() {
+                push(CHANNEL_KIND, "Channel", RUST, &channel);
+                let mut all = self.requirements.lock().unwrap();
+                all.insert(path_id, requirements);
+            } else {
+                log::debug!("{path}: skipping floating channel {channel}");
+            }
+            return;
+        }
+
+        // Applying an update rewrites every step pinning the same version
+        let mut seen = HashSet::new();
+        for scalar in install_action_tools(&text, path) {
+            for (name, version) in tool_pins(&scalar.value) {
+                if seen.insert((name.to_owned(), version.to_owned())) {
+                    push(TOOL_KIND, "Tool", name, version);
+                }
+            }
+        }
+    }
+
+    fn find_updates(
+        &self,
+        dep: &Dep,
+        notes: &mut Vec<String>,
+        candidates: &mut Vec<Candidate>,
+    ) -> Updates {
+        let Version::SemVer(current) = &dep.version else {
+            unreachable!()
+        };
+
+        // The channel is always named `rust`, which no installable tool is
+        if dep.name == RUST {
+            self.find_channel_updates(dep, current, notes)
+        } else {
+            self.find_tool_updates(dep, current, candidates)
+        }
+    }
+
+    fn apply(&self, deps: &Deps, dep: &Dep, version: &Version) {
+        let (Version::SemVer(current), Version::SemVer(latest)) = (&dep.version, version) else {
+            unreachable!()
+        };
+
+        let path = deps.path(dep.path.unwrap());
+        let mut text = fs::read_to_string(path).unwrap();
+
+        if deps.internal_kind(dep.kind.unwrap()) == CHANNEL_KIND {
+            match read_toolchain(&text) {
+                Some((channel, span, _)) if channel == *current => {
+                    text.replace_range(span, latest);
+                }
+                _ => {
+                    log::warn!("{path}: could not find channel {current}");
+                    return;
+                }
+            }
+        } else {
+            let pin = format!("{}@{current}", dep.name);
+            let scalars = install_action_tools(&text, path)
+                .into_iter()
+                .filter(|scalar| tool_pins(&scalar.value).any(|p| p == (&dep.name, current)))
+                .collect::<Vec<_>>();
+            if scalars.is_empty() {
+                log::warn!("{path}: could not find tool {pin}");
+                return;
+            }
+
+            for scalar in scalars.iter().rev() {
+                let value = scalar
+                    .value
+                    .split(',')
+                    .map(|tool| {
+                        if tool.trim() == pin {
+                            tool.replace(&pin, &format!("{}@{latest}", dep.name))
+                        } else {
+                            tool.to_owned()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                yaml::replace(&mut text, scalar, &value);
+            }
+        }
+
+        fs::write(path, text).unwrap();
+    }
+}
+
+/// A channel pinned to a specific toolchain
+enum Channel<'a> {
+    /// A stable release like `1.83.0` or `1.83`
+    Release(&'a str),
+    /// A dated channel like `nightly-2024-11-01`
+    Dated { channel: &'a str, date: &'a str },
+}
+
+impl<'a> Channel<'a> {
+    fn parse(channel: &'a str) -> Option<Self> {
+        if release_parts(channel).is_some_and(|parts| parts.len() >= 2) {
+            return Some(Self::Release(channel));
+        }
+
+        let (name, date) = channel.split_once('-')?;
+        let is_date = date.len() == 10
+            && date.char_indices().all(|(i, c)| {
+                if i == 4 || i == 7 {
+                    c == '-'
+                } else {
+                    c.is_ascii_digit()
+                }
+            });
+        (matches!(name, "stable" | "beta" | "nightly") && is_date).then_some(Self::Dated {
+            channel: name,
+            date,
+        })
+    }
+}
+
+/// Reads the channel from a toolchain file, along with the byte range of its
+/// value and the components and targets it requires. Legacy files contain
+/// nothing but the channel.
+fn read_toolchain(text: &str) -> Option<(String, Range<usize>, Requirements)> {
+    let trimmed = text.trim();
+    if !trimmed.is_empty() && !trimmed.contains(['[', '=', '\n']) {
+        let start = text.find(trimmed).unwrap();
+        return Some((
+            trimmed.to_owned(),
+            start..start + trimmed.len(),
+            Requirements::default(),
+        ));
+    }
+
+    let dom = taplo::parser::parse(text).into_dom();
+    let toolchain = dom.as_table()?.get("toolchain")?;
+    let toolchain = toolchain.as_table()?;
+    let channel = toolchain.get("channel")?;
+    let channel = channel.as_str()?;
+
+    // Strip the quotes
+    let range = channel.syntax()?.text_range();
+    let span = usize::from(range.start()) + 1..usize::from(range.end()) - 1;
+
+    let list = |key| match toolchain.get(key) {
+        Some(Node::Array(array)) => array
+            .items()
+            .read()
+            .iter()
+            .filter_map(|item| Some(item.as_str()?.value().to_owned()))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let requirements = Requirements {
+        components: list("components"),
+        targets: list("targets"),
+    };
+    Some((channel.value().to_owned(), span, requirements))
+}
+
+/// Finds the `tool` inputs of `taiki-e/install-action` steps.
+fn install_action_tools(text: &str, path: &Utf8Path) -> Vec<Scalar> {
+    let scalars = match yaml::scalars(text) {
+        Ok(scalars) => scalars,
+        Err(err) => {
+            log::warn!("{path}: failed to parse: {err}");
+            return Vec::new();
+        }
+    };
+
+    let uses = Segment::Key("uses".to_owned());
+    let steps = scalars
+        .iter()
+        .filter(|(at, scalar)| at.last() == Some(&uses) && scalar.value.starts_with(INSTALL_ACTION))
+        .map(|(at, _)| at[..at.len() - 1].to_vec())
+        .collect::<Vec<_>>();
+
+    let tool = [
+        Segment::Key("with".to_owned()),
+        Segment::Key("tool".to_owned()),
+    ];
+    scalars
+        .into_iter()
+        .filter(|(at, _)| {
+            at.ends_with(&tool) && steps.iter().any(|step| **step == at[..at.len() - 2])
+        })
+        .map(|(_, scalar)| scalar)
+        .collect()
+}
+
+/// Parses a list of tools like `cargo-shear@1.1.0,cargo-nextest`, returning
+/// those pinned to a release.
+fn tool_pins(tools: &str) -> impl Iterator<Item = (&str, &str)> {
+    tools
+        .split(',')
+        .filter_map(|tool| tool.trim().split_once('@'))
+        .filter(|(_, version)| release_parts(version).is_some())
+}
+
+/// Parses a release like `1.2.3`, rejecting pre-releases.
+fn release_parts(version: &str) -> Option<Vec<u64>> {
+    version
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<_>>>()
+        .filter(|parts| (1..=3).contains(&parts.len()))
+}
+
+/// Shortens `latest` to as many components as `current` has, returning it if
+/// it is newer.
+fn newer(current: &str, latest: &str) -> Option<String> {
+    let current = release_parts(current)?;
+    let latest = latest.split('.').take(current.len()).collect::<Vec<_>>();
+    let latest_parts = release_parts(&latest.join("."))?;
+    (latest_parts > current).then(|| latest.join("."))
+}
@@ -0,0 +1,193 @@
+//! Reads the channel manifests rustup installs toolchains from.
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+
+use taplo::dom::Node;
+
+static DEFAULT_SERVER: &str = "https://static.rust-lang.org";
+
+pub(in crate::managers) struct Server {
+    url: String,
+}
+
+pub(in crate::managers) struct Manifest {
+    pub(super) date: String,
+    /// Version of the `rust` package, like `1.83.0`
+    pub(in crate::managers) version: Option<String>,
+    /// Packages and the targets they are available for
+    available: HashSet<(String, String)>,
+    /// Component names and the packages they were renamed to
+    renames: HashMap<String, String>,
+}
+
+impl Server {
+    /// Uses the configured server, then `RUSTUP_DIST_SERVER`.
+    pub(in crate::managers) fn new(configured: Option<&str>) -> Self {
+        let url = configured
+            .map(str::to_owned)
+            .or_else(|| env::var("RUSTUP_DIST_SERVER").ok())
+            .unwrap_or_else(|| DEFAULT_SERVER.to_owned());
+        let url = url.trim_end_matches('/').to_owned();
+        Self { url }
+    }
+
+    /// Fetches the manifest of `channel` from `date`, or the latest one.
+    pub(in crate::managers) fn manifest(
+        &self,
+        channel: &str,
+        date: Option<&str>,
+    ) -> Option<Manifest> {
+        let url = match date {
+            Some(date) => format!("{}/dist/{date}/channel-rust-{channel}.toml", self.url),
+            None => format!("{}/dist/channel-rust-{channel}.toml", self.url),
+        };
+
+        let mut response = match ureq::get(&url).call() {
+            Ok(response) => response,
+            Err(ureq::Error::StatusCode(404)) => {
+                log::debug!("no manifest at {url}");
+                return None;
+            }
+            Err(err) => {
+                log::warn!("failed to fetch {url}: {err}");
+                return None;
+            }
+        };
+
+        let body = response
+            .body_mut()
+            .with_config()
+            .limit(u64::MAX)
+            .read_to_string()
+            .ok()?;
+        let manifest = Manifest::parse(&body);
+        if manifest.is_none() {
+            log::warn!("could not parse {url}");
+        }
+        manifest
+    }
+}
+
+impl Manifest {
+    fn parse(toml: &str) -> Option<Self> {
+        let dom = taplo::parser::parse(toml).into_dom();
+        let root = dom.as_table()?;
+        let date = root.get("date")?.as_str()?.value().to_owned();
+
+        let mut version = None;
+        let mut available = HashSet::new();
+        if let Some(Node::Table(packages)) = root.get("pkg") {
+            for (name, package) in packages.entries().read().iter() {
+                let Node::Table(package) = package else {
+                    continue;
+                };
+
+                if name.value() == "rust" {
+                    version = package
+                        .get("version")
+                        .and_then(|version| version.as_str().map(|v| v.value().to_owned()))
+                        .and_then(|version| Some(version.split(' ').next()?.to_owned()));
+                }
+
+                let Some(Node::Table(targets)) = package.get("target") else {
+                    continue;
+                };
+                for (target, info) in targets.entries().read().iter() {
+                    let is_available = info
+                        .as_table()
+                        .and_then(|info| info.get("available"))
+                        .and_then(|available| Some(available.as_bool()?.value()))
+                        .unwrap_or(false);
+                    if is_available {
+                        available.insert((name.value().to_owned(), target.value().to_owned()));
+                    }
+                }
+            }
+        }
+
+        let mut renames = HashMap::new();
+        if let Some(Node::Table(table)) = root.get("renames") {
+            for (from, to) in table.entries().read().iter() {
+                if let Some(to) = to.as_table().and_then(|to| to.get("to"))
+                    && let Some(to) = to.as_str()
+                {
+                    renames.insert(from.value().to_owned(), to.value().to_owned());
+                }
+            }
+        }
+
+        Some(Self {
+            date,
+            version,
+            available,
+            renames,
+        })
+    }
+
+    /// Lists which of the toolchain, `components`, and the standard library
+    /// for `targets` aren't available for this host.
+    pub(super) fn missing(&self, components: &[String], targets: &[String]) -> Vec<String> {
+        let host = host_triple();
+        let has = |package: &str, target: &str| {
+            let package = self.renames.get(package).map_or(package, String::as_str);
+            self.available
+                .contains(&(package.to_owned(), target.to_owned()))
+        };
+
+        let mut missing = Vec::new();
+        if !has("rust", &host) {
+            missing.push("rust".to_owned());
+        }
+        for component in components {
+            if !has(component, &host) && !has(component, "*") {
+                missing.push(component.clone());
+            }
+        }
+        for target in targets {
+            if !has("rust-std", target) {
+                missing.push(format!("rust-std ({target})"));
+            }
+        }
+        missing
+    }
+}
+
+/// Approximates the target triple rustup would pick on this machine.
+fn host_triple() -> String {
+    let (arch, os) = (env::consts::ARCH, env::consts::OS);
+    match os {
+        "linux" => format!("{arch}-unknown-linux-gnu"),
+        "macos" => format!("{arch}-apple-darwin"),
+        "windows" => format!("{arch}-pc-windows-msvc"),
+        _ => format!("{arch}-unknown-{os}"),
+    }
+}
+
+/// Returns the day before a `YYYY-MM-DD` date.
+pub(super) fn previous_day(date: &str) -> Option<String> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+    let (mut year, mut month, mut day) = (parts.next()??, parts.next()??, parts.next()??);
+
+    if day > 1 {
+        day -= 1;
+    } else {
+        if month > 1 {
+            month -= 1;
+        } else {
+            month = 12;
+            year -= 1;
+        }
+
+        let leap =
+            year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        day = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+    }
+
+    Some(format!("{year:04}-{month:02}-{day:02}"))
+}
@@ -1,9 +1,13 @@
 use std::io::{self, Write};
 
+use crate::Manager;
 use crate::dep_collector::{Dep, Deps, Updates};
 
-pub(crate) fn write_markdown(collector: &Deps, out: &mut impl Write) -> io::Result<()> {
-    let managers = crate::managers::all();
+pub fn write_markdown(
+    collector: &Deps,
+    managers: &[Box<dyn Manager>],
+    out: &mut impl Write,
+) -> io::Result<()> {
     let mut table = collector.deps().iter().collect::<Vec<_>>();
 
     let get_path_str = |dep: &Dep| {
@@ -25,7 +29,7 @@ pub(crate) fn write_markdown(collector: &Deps, out: &mut impl Write) -> io::Resu
 
     writeln!(out, "|    | Name | Old | New | Manager | Path | Kind |")?;
     writeln!(out, "|:---|:-----|:----|:----|:--------|:-----|:-----|")?;
-    for row in table {
+    for row in &table {
         let status = if row.skip {
             "❌"
         } else if row.updates.is_none() {
@@ -48,15 +52,39 @@ pub(crate) fn write_markdown(collector: &Deps, out: &mut impl Write) -> io::Resu
         }
 
         let has_path = row.path.is_some();
-        writeln!(
+        write!(
             out,
-            " | {manager} | {p_tick}{p_slash}{path}{p_tick} | {kind} |",
+            " | {manager} | {p_tick}{p_slash}{path}{p_tick}",
             manager = managers[row.manager].name(),
             path = get_path_str(row),
             p_tick = if has_path { "`" } else { "" },
             p_slash = if has_path { "/" } else { "" },
-            kind = get_kind_str(row),
         )?;
+
+        if !row.members.is_empty() {
+            write!(out, "<br>inherited by ")?;
+            for (i, member) in row.members.iter().enumerate() {
+                if i != 0 {
+                    write!(out, ", ")?;
+                }
+                let path = member.path.map(|id| collector.path(id).as_str());
+                write!(out, "`/{}`", path.unwrap_or_default())?;
+            }
+        }
+
+        writeln!(out, " | {kind} |", kind = get_kind_str(row))?;
+    }
+
+    let mut noted = table.iter().filter(|dep| !dep.notes.is_empty()).peekable();
+    if noted.peek().is_some() {
+        writeln!(out)?;
+        writeln!(out, "**Notes:**")?;
+        writeln!(out)?;
+    }
+    for dep in noted {
+        for note in &dep.notes {
+            writeln!(out, "- `{}` in `/{}`: {note}", dep.name, get_path_str(dep))?;
+        }
     }
 
     Ok(())
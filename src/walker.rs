@@ -1,28 +1,97 @@
+use std::collections::HashSet;
 use std::fs;
 use std::io;
+use std::num::NonZero;
 use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
 
 use camino::{Utf8Path, Utf8PathBuf};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 
-use crate::Manager;
+use crate::config::Config;
+use crate::{Filter, Manager};
 
-pub(crate) fn walk(root: &Utf8Path, managers: &[Box<dyn Manager>]) -> Box<[Vec<Utf8PathBuf>]> {
-    let raw = Walker::new(root, managers).walk();
+pub fn walk(
+    root: &Utf8Path,
+    managers: &[Box<dyn Manager>],
+    config: &Config,
+    filter: &Filter,
+) -> Box<[Vec<Utf8PathBuf>]> {
+    let raw = Walker::new(root, managers, config, filter).walk();
 
     let mut sorted = std::iter::repeat_n(Vec::new(), managers.len()).collect::<Vec<_>>();
 
     for (id, path) in raw {
-        sorted[usize::from(id)].push(path);
+        sorted[id].push(path);
     }
 
+    // Files reached through several symlinks have the same real path
     for paths in &mut sorted {
         paths.sort();
+        paths.dedup();
     }
 
     sorted.into_boxed_slice()
 }
 
-type ManagerSet = u8;
+/// Managers still interested in a directory, by id
+#[derive(Clone)]
+struct ManagerSet(Vec<u64>);
+
+impl ManagerSet {
+    fn empty(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
+    }
+
+    fn insert(&mut self, id: usize) {
+        self.0[id / 64] |= 1 << (id % 64);
+    }
+
+    fn contains(&self, id: usize) -> bool {
+        self.0[id / 64] & (1 << (id % 64)) != 0
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.iter().all(|&bits| bits == 0)
+    }
+}
+
+/// The globs of every manager compiled together, so each path is matched once
+struct Patterns {
+    set: GlobSet,
+    /// Manager id of each glob in `set`
+    owners: Vec<usize>,
+    managers: usize,
+}
+
+impl Patterns {
+    fn new<'a>(managers: usize, globs: impl IntoIterator<Item = (usize, &'a Glob)>) -> Self {
+        let mut set = GlobSetBuilder::new();
+        let mut owners = Vec::new();
+        for (id, glob) in globs {
+            set.add(glob.clone());
+            owners.push(id);
+        }
+        Self {
+            set: set.build().unwrap(),
+            owners,
+            managers,
+        }
+    }
+
+    /// Finds the managers in `enabled` with a glob matching `path`.
+    fn matches(&self, path: &Utf8Path, enabled: &ManagerSet) -> ManagerSet {
+        let mut matched = ManagerSet::empty(self.managers);
+        for glob in self.set.matches(path) {
+            let id = self.owners[glob];
+            if enabled.contains(id) {
+                matched.insert(id);
+            }
+        }
+        matched
+    }
+}
 
 const IGNORE_SETTINGS: gix::ignore::search::Ignore = gix::ignore::search::Ignore {
     support_precious: false,
@@ -31,46 +100,202 @@ static IMPLICIT_IGNORES: &[u8] = br"
 .git/
 .gitignore
 ";
+/// Read in every directory, with later files taking precedence
+static IGNORE_FILES: &[&str] = &[".gitignore", concat!(".", env!("CARGO_PKG_NAME"), "ignore")];
 
 struct Walker<'a> {
     root: &'a Utf8Path,
-    managers: &'a [Box<dyn Manager>],
+    filter: &'a Filter,
+    /// Path from the repository containing `root` to `root`, as ignore
+    /// patterns are relative to the repository
+    prefix: Utf8PathBuf,
+    names: Vec<&'static str>,
+    nested_repositories: bool,
+    follow_symlinks: bool,
+    /// `root` with symlinks resolved, if following them
+    real_root: Utf8PathBuf,
+    /// Real paths of the symlinked directories walked so far, and the root
+    visited: Mutex<HashSet<Utf8PathBuf>>,
 
-    ignore: gix::ignore::Search,
-    out: Vec<(ManagerSet, Utf8PathBuf)>,
+    files: Patterns,
+    directories: Patterns,
+    queue: Mutex<Queue>,
+    /// Signalled whenever the queue changes
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct Queue {
+    dirs: Vec<Dir>,
+    /// Number of directories being walked, which may queue more
+    busy: usize,
+}
+
+struct Dir {
+    path: Utf8PathBuf,
+    /// Where the directory is after resolving symlinks along `path`
+    real: Utf8PathBuf,
+    enabled: ManagerSet,
+    /// Patterns from the repository and every parent directory
+    ignore: Arc<gix::ignore::Search>,
 }
 
 impl<'a> Walker<'a> {
-    fn new(root: &'a Utf8Path, managers: &'a [Box<dyn Manager>]) -> Self {
-        let mut ignore = gix::ignore::Search::from_git_dir(
-            &root.join(".git").into_std_path_buf(),
-            None,
-            &mut Vec::new(),
-            IGNORE_SETTINGS,
-        )
-        .unwrap();
-        ignore.add_patterns_buffer(
-            IMPLICIT_IGNORES,
-            "<implicit ignores>",
-            None,
-            IGNORE_SETTINGS,
-        );
+    fn new(
+        root: &'a Utf8Path,
+        managers: &[Box<dyn Manager>],
+        config: &Config,
+        filter: &'a Filter,
+    ) -> Self {
+        let interests = managers
+            .iter()
+            .map(|manager| manager.interest())
+            .collect::<Vec<_>>();
+        let globs = |select: fn(&crate::managers::Interest) -> &Vec<Glob>| {
+            let globs = interests
+                .iter()
+                .enumerate()
+                .flat_map(move |(id, interest)| {
+                    select(interest).iter().map(move |glob| (id, glob))
+                });
+            Patterns::new(managers.len(), globs)
+        };
 
-        // TODO: add global git ignore
+        let follow_symlinks = config.walk.follow_symlinks;
+        let real_root = if follow_symlinks {
+            root.canonicalize_utf8().unwrap()
+        } else {
+            root.to_owned()
+        };
 
         Self {
             root,
-            managers,
+            filter,
+            prefix: Utf8PathBuf::new(),
+            names: managers.iter().map(|manager| manager.name()).collect(),
+            nested_repositories: config.walk.nested_repositories,
+            follow_symlinks,
+            visited: Mutex::new(HashSet::from([real_root.clone()])),
+            real_root,
+            files: globs(|interest| &interest.files),
+            directories: globs(|interest| &interest.directories),
+            queue: Mutex::default(),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn walk(mut self) -> Vec<(usize, Utf8PathBuf)> {
+        let ignore = self.repository_ignores();
+        let mut enabled = ManagerSet::empty(self.names.len());
+        for (id, name) in self.names.iter().enumerate() {
+            if self.filter.includes_manager(name) {
+                enabled.insert(id);
+            }
+        }
+        self.queue.get_mut().unwrap().dirs.push(Dir {
+            path: self.root.to_owned(),
+            real: self.real_root.clone(),
+            enabled,
             ignore,
-            out: Vec::new(),
+        });
+
+        let threads = thread::available_parallelism().map_or(1, NonZero::get);
+        thread::scope(|scope| {
+            let workers = (0..threads)
+                .map(|_| scope.spawn(|| self.work()))
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        })
+    }
+
+    /// Finds the repository containing `root`, and loads the patterns from
+    /// the global excludes file, `info/exclude`, and ignore files in the
+    /// directories above `root`.
+    fn repository_ignores(&mut self) -> Arc<gix::ignore::Search> {
+        let mut ignore = Arc::default();
+        let repo = match gix::discover(self.root) {
+            Ok(repo) => Some(repo),
+            Err(err) => {
+                log::debug!("not in a git repository: {err}");
+                None
+            }
+        };
+
+        if let Some(repo) = &repo
+            && let Some(workdir) = repo.workdir()
+            && let Ok(root) = self.root.canonicalize_utf8()
+            && let Ok(workdir) = workdir.canonicalize()
+            && let Ok(prefix) = root.strip_prefix(&workdir)
+        {
+            self.prefix = prefix.to_owned();
+
+            let excludes_file = match repo.config_snapshot().trusted_path("core.excludesFile") {
+                Some(Ok(path)) => Some(path.into_owned()),
+                Some(Err(err)) => {
+                    log::warn!("failed to read core.excludesFile: {err}");
+                    None
+                }
+                None => default_excludes_file(),
+            };
+            match gix::ignore::Search::from_git_dir(
+                repo.common_dir(),
+                excludes_file,
+                &mut Vec::new(),
+                IGNORE_SETTINGS,
+            ) {
+                Ok(search) => ignore = Arc::new(search),
+                Err(err) => log::warn!("failed to read excludes: {err}"),
+            }
+
+            // Outermost first, so nearer ones take precedence
+            let mut parents = root
+                .ancestors()
+                .zip(self.prefix.ancestors())
+                .skip(1)
+                .collect::<Vec<_>>();
+            parents.reverse();
+            for (dir, relative) in parents {
+                self.add_ignore_files(&mut ignore, dir, relative);
+            }
         }
+
+        Arc::make_mut(&mut ignore).add_patterns_buffer(
+            IMPLICIT_IGNORES,
+            "<implicit ignores>",
+            None,
+            IGNORE_SETTINGS,
+        );
+        ignore
     }
 
-    fn walk(mut self) -> Vec<(ManagerSet, Utf8PathBuf)> {
-        // FIXME: handle overflow
-        let all_managers = (1 << self.managers.len() as u8) - 1;
-        self.step(self.root, all_managers);
-        self.out
+    /// Walks directories from the queue until there are none left.
+    fn work(&self) -> Vec<(usize, Utf8PathBuf)> {
+        let mut out = Vec::new();
+        loop {
+            let dir = {
+                let mut queue = self.queue.lock().unwrap();
+                loop {
+                    if let Some(dir) = queue.dirs.pop() {
+                        queue.busy += 1;
+                        break dir;
+                    }
+                    if queue.busy == 0 {
+                        return out;
+                    }
+                    queue = self.changed.wait(queue).unwrap();
+                }
+            };
+
+            let children = self.step(dir, &mut out);
+
+            let mut queue = self.queue.lock().unwrap();
+            queue.busy -= 1;
+            queue.dirs.extend(children);
+            self.changed.notify_all();
+        }
     }
 }
 
@@ -89,53 +314,69 @@ impl Walker<'_> {
         })
     }
 
-    fn add_ignore_file(&mut self, path: &Utf8Path) -> io::Result<()> {
-        let contents = match std::fs::read(path) {
-            Ok(bytes) => bytes,
-            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
-            Err(err) => return Err(err),
-        };
-
-        self.ignore.add_patterns_buffer(
-            &contents,
-            self.relative(path).unwrap(),
-            Some(Path::new("")),
-            IGNORE_SETTINGS,
-        );
-
-        Ok(())
+    /// Adds the patterns from the ignore files in `dir`, which is at
+    /// `relative` in the repository. The patterns are only copied when `dir`
+    /// has ignore files of its own.
+    fn add_ignore_files(
+        &self,
+        ignore: &mut Arc<gix::ignore::Search>,
+        dir: &Utf8Path,
+        relative: &Utf8Path,
+    ) {
+        for name in IGNORE_FILES {
+            let path = dir.join(name);
+            match fs::read(&path) {
+                Ok(contents) => Arc::make_mut(ignore).add_patterns_buffer(
+                    &contents,
+                    relative.join(name),
+                    Some(Path::new("")),
+                    IGNORE_SETTINGS,
+                ),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => log::warn!(
+                    "failed to read ignore file {}: {err}",
+                    self.display_path(&path)
+                ),
+            }
+        }
     }
 
-    fn is_ignored(&self, path: &Utf8Path, is_dir: bool) -> bool {
+    fn is_ignored(&self, ignore: &gix::ignore::Search, path: &Utf8Path, is_dir: bool) -> bool {
         let case = gix::ignore::glob::pattern::Case::Sensitive;
-        self.ignore
+        let path = self.prefix.join(path);
+        ignore
             .pattern_matching_relative_path(path.as_str().into(), Some(is_dir), case)
-            .is_some()
+            .is_some_and(|found| !found.pattern.is_negative())
     }
 
-    fn step(&mut self, dir: &Utf8Path, enabled: ManagerSet) {
-        log::trace!("entering {}", self.display_path(dir));
+    /// Finds the files in a directory, returning the directories in it to
+    /// walk next.
+    fn step(&self, dir: Dir, out: &mut Vec<(usize, Utf8PathBuf)>) -> Vec<Dir> {
+        let Dir {
+            path: dir,
+            real: real_dir,
+            enabled,
+            mut ignore,
+        } = dir;
+        log::trace!("entering {}", self.display_path(&dir));
 
-        let local_ignore = dir.join(".gitignore");
-        if let Err(err) = self.add_ignore_file(&local_ignore) {
-            log::warn!(
-                "failed to read ignore file {}: {err}",
-                self.display_path(&local_ignore)
-            );
-        }
+        let relative = self.prefix.join(self.relative(&dir).unwrap());
+        self.add_ignore_files(&mut ignore, &dir, &relative);
 
-        let entries = match std::fs::read_dir(dir) {
+        let entries = match std::fs::read_dir(&dir) {
             Ok(entries) => entries,
             Err(err) => {
-                log::warn!("failed to open dir {}: {err}", self.display_path(dir));
-                return;
+                log::warn!("failed to open dir {}: {err}", self.display_path(&dir));
+                return Vec::new();
             }
         };
+
+        let mut children = Vec::new();
         for entry in entries {
             let entry = match entry {
                 Ok(entry) => entry,
                 Err(err) => {
-                    log::warn!("failed to read entry of {}: {err}", self.display_path(dir));
+                    log::warn!("failed to read entry of {}: {err}", self.display_path(&dir));
                     continue;
                 }
             };
@@ -147,9 +388,24 @@ impl Walker<'_> {
                 log::warn!("failed to read type of file: {relative}");
                 continue;
             };
-            let file_type = FileType::from(file_type);
+            let mut file_type = FileType::from(file_type);
+            let mut real = real_dir.join(path.file_name().unwrap());
+            let symlink = file_type == FileType::Symlink;
 
-            if self.is_ignored(relative, file_type == FileType::Directory) {
+            if symlink && self.follow_symlinks {
+                match (fs::metadata(&path), path.canonicalize_utf8()) {
+                    (Ok(metadata), Ok(target)) => {
+                        file_type = FileType::from(metadata.file_type());
+                        real = target;
+                    }
+                    (Err(err), _) | (_, Err(err)) => {
+                        log::warn!("failed to follow symlink {relative}: {err}");
+                        continue;
+                    }
+                }
+            }
+
+            if self.is_ignored(&ignore, relative, file_type == FileType::Directory) {
                 log::debug!("ignoring {relative}");
                 continue;
             }
@@ -162,34 +418,65 @@ impl Walker<'_> {
                     log::warn!("skipping symlink: {relative}");
                 }
                 FileType::Directory => {
-                    let mut new_enabled = enabled;
-                    for (id, manager) in self.managers.iter().enumerate() {
-                        let mask = 1 << (id as ManagerSet);
-                        if (enabled & mask) > 0 && !manager.walk_directory(relative) {
-                            log::debug!("{}: disabling in {relative}", manager.name());
-                            new_enabled ^= mask;
+                    // Submodules and nested repositories are managed on their own
+                    if !self.nested_repositories
+                        && let Ok(true) = fs::exists(path.join(".git"))
+                    {
+                        log::debug!("skipping repository {relative}");
+                        continue;
+                    }
+
+                    // Catches cycles, and targets of several symlinks. Real
+                    // directories are always walked, so which path a
+                    // directory is found through doesn't depend on timing.
+                    if symlink && !self.visited.lock().unwrap().insert(real.clone()) {
+                        log::debug!("already walked {relative}");
+                        continue;
+                    }
+
+                    let new_enabled = self.directories.matches(relative, &enabled);
+                    for (id, name) in self.names.iter().enumerate() {
+                        if enabled.contains(id) && !new_enabled.contains(id) {
+                            log::debug!("{name}: disabling in {relative}");
                         }
                     }
 
-                    if new_enabled > 0 {
-                        self.step(&path, new_enabled);
+                    if !new_enabled.is_empty() {
+                        children.push(Dir {
+                            path,
+                            real,
+                            enabled: new_enabled,
+                            ignore: Arc::clone(&ignore),
+                        });
                     }
                 }
                 FileType::File => {
-                    for (id, manager) in self.managers.iter().enumerate() {
-                        let id = id as ManagerSet;
-                        let mask = 1 << id;
-                        if (enabled & mask) > 0 && manager.walk_file(relative) {
-                            log::debug!("{}: registering {relative}", manager.name());
-                            self.out.push((id, relative.to_owned()));
+                    // Edits should go to the file itself, wherever it is
+                    let real = real
+                        .strip_prefix(&self.real_root)
+                        .map_or(real.as_path(), |relative| relative);
+                    let matched = self.files.matches(relative, &enabled);
+                    for (id, name) in self.names.iter().enumerate() {
+                        if matched.contains(id) {
+                            log::debug!("{name}: registering {relative}");
+                            out.push((id, real.to_owned()));
                         }
                     }
                 }
             }
         }
+        children
     }
 }
 
+/// Git's default for `core.excludesFile`
+fn default_excludes_file() -> Option<std::path::PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| Some(dirs::home_dir()?.join(".config")))?;
+    Some(config.join("git").join("ignore"))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum FileType {
     Directory,
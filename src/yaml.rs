@@ -0,0 +1,192 @@
+//! Minimal YAML support for reading string mappings and editing their values
+//! in place, without reformatting the rest of the file.
+
+use std::ops::Range;
+
+use yaml_rust2::parser::{Event, Parser};
+use yaml_rust2::scanner::TScalarStyle;
+
+pub(crate) struct Scalar {
+    pub(crate) value: String,
+    style: TScalarStyle,
+    /// Byte range of the value in the source, including any quotes
+    span: Range<usize>,
+}
+
+impl Scalar {
+    /// Byte offset in the source just past the value and any quotes.
+    pub(crate) fn end(&self) -> usize {
+        self.span.end
+    }
+}
+
+/// One step of the path to a value: a mapping key or a sequence index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+enum Frame {
+    Mapping { key: Option<String> },
+    Sequence { index: usize },
+}
+
+impl Frame {
+    /// Moves past the current value.
+    fn advance(&mut self) {
+        match self {
+            Self::Mapping { key } => *key = None,
+            Self::Sequence { index } => *index += 1,
+        }
+    }
+}
+
+/// Returns the scalar entries of the mapping found by following `path` from
+/// the root of the first document. Nested collections are skipped.
+pub(crate) fn mapping(source: &str, path: &[&str]) -> Result<Vec<(String, Scalar)>, String> {
+    let entries = scalars(source)?
+        .into_iter()
+        .filter_map(|(mut at, scalar)| {
+            let Some(Segment::Key(key)) = at.pop() else {
+                return None;
+            };
+            let matches = at.len() == path.len()
+                && at
+                    .iter()
+                    .zip(path)
+                    .all(|(segment, expected)| matches!(segment, Segment::Key(k) if k == expected));
+            matches.then_some((key, scalar))
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// Returns every scalar value in the first document along with its path.
+/// Mapping keys aren't included.
+pub(crate) fn scalars(source: &str) -> Result<Vec<(Vec<Segment>, Scalar)>, String> {
+    let mut parser = Parser::new_from_str(source);
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut scalars = Vec::new();
+
+    let path = |stack: &[Frame]| {
+        stack
+            .iter()
+            .filter_map(|frame| match frame {
+                Frame::Mapping { key } => Some(Segment::Key(key.clone()?)),
+                Frame::Sequence { index } => Some(Segment::Index(*index)),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    loop {
+        let (event, marker) = parser.next_token().map_err(|err| err.to_string())?;
+        match event {
+            Event::StreamEnd | Event::DocumentEnd => break,
+            Event::MappingStart(..) => stack.push(Frame::Mapping { key: None }),
+            Event::SequenceStart(..) => stack.push(Frame::Sequence { index: 0 }),
+            Event::MappingEnd | Event::SequenceEnd => {
+                stack.pop();
+                if let Some(frame) = stack.last_mut() {
+                    frame.advance();
+                }
+            }
+            Event::Scalar(value, style, ..) => {
+                if let Some(Frame::Mapping { key: key @ None }) = stack.last_mut() {
+                    *key = Some(value);
+                    continue;
+                }
+
+                let path = path(&stack);
+                let start = byte_index(source, marker.index());
+                match scalar_end(source, start, &value, style) {
+                    Some(end) => scalars.push((
+                        path,
+                        Scalar {
+                            value,
+                            style,
+                            span: start..end,
+                        },
+                    )),
+                    None => log::debug!("skipping unsupported YAML value at {path:?}"),
+                }
+
+                if let Some(frame) = stack.last_mut() {
+                    frame.advance();
+                }
+            }
+            Event::Alias(_) => match stack.last_mut() {
+                Some(Frame::Mapping { key: key @ None }) => *key = Some(String::new()),
+                Some(frame) => frame.advance(),
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    Ok(scalars)
+}
+
+/// Replaces `scalar` in `source` with `value`, keeping its quoting style.
+pub(crate) fn replace(source: &mut String, scalar: &Scalar, value: &str) {
+    let quoted = match scalar.style {
+        TScalarStyle::DoubleQuoted => {
+            format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+        TScalarStyle::SingleQuoted => format!("'{}'", value.replace('\'', "''")),
+        _ if needs_quotes(value) => format!("'{}'", value.replace('\'', "''")),
+        _ => value.to_owned(),
+    };
+
+    source.replace_range(scalar.span.clone(), &quoted);
+}
+
+fn byte_index(source: &str, chars: usize) -> usize {
+    source
+        .char_indices()
+        .nth(chars)
+        .map_or(source.len(), |(i, _)| i)
+}
+
+/// Finds the end of a single-line scalar starting at `start`.
+fn scalar_end(source: &str, start: usize, value: &str, style: TScalarStyle) -> Option<usize> {
+    let rest = &source[start..];
+    let end = match style {
+        TScalarStyle::Plain => rest.starts_with(value).then_some(value.len())?,
+        TScalarStyle::SingleQuoted => {
+            let mut chars = rest.char_indices().skip(1).peekable();
+            loop {
+                match chars.next()? {
+                    (_, '\'') if chars.next_if(|(_, c)| *c == '\'').is_some() => {}
+                    (i, '\'') => break i + 1,
+                    (_, '\n') => return None,
+                    _ => {}
+                }
+            }
+        }
+        TScalarStyle::DoubleQuoted => {
+            let mut chars = rest.char_indices().skip(1);
+            loop {
+                match chars.next()? {
+                    (_, '\\') => {
+                        chars.next();
+                    }
+                    (i, '"') => break i + 1,
+                    (_, '\n') => return None,
+                    _ => {}
+                }
+            }
+        }
+        TScalarStyle::Literal | TScalarStyle::Folded => return None,
+    };
+
+    Some(start + end)
+}
+
+/// Whether a plain scalar would be misread, eg `>=1.0.0` as a folded block.
+fn needs_quotes(value: &str) -> bool {
+    value.starts_with([
+        '>', '|', '!', '&', '*', '%', '@', '`', '\'', '"', '{', '[', '#', '-', '?', ':', ',',
+    ]) || value.contains(": ")
+        || value.contains(" #")
+}